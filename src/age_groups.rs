@@ -0,0 +1,85 @@
+// Age groups are given by where each one starts, e.g., 20,30,40 is
+// under 20, 20-29, 30-39 and 40 and over, since that's how race
+// directors describe them and it can't leave a gap or an overlap.
+
+use {
+    anyhow::{Result, anyhow, bail},
+    std::str::FromStr,
+};
+
+/// Where each age group starts; see --age-groups.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgeGroups {
+    starts: Vec<u8>,
+}
+
+impl Default for AgeGroups {
+    // Ten years each, from 20 to 90.
+    fn default() -> Self {
+        Self {
+            starts: (20..=90).step_by(10).collect(),
+        }
+    }
+}
+
+impl FromStr for AgeGroups {
+    type Err = anyhow::Error;
+
+    fn from_str(starts: &str) -> Result<Self> {
+        let starts = starts
+            .split(',')
+            .map(|start| {
+                start
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("{start:?} isn't an age"))
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        if starts.windows(2).any(|pair| pair[0] >= pair[1]) || starts.first() == Some(&0) {
+            bail!("age groups must start at increasing ages above 0, e.g., 20,30,40");
+        }
+        Ok(Self { starts })
+    }
+}
+
+impl AgeGroups {
+    /// The index of the group that age is in, which is also where it
+    /// sorts, and its label.
+    pub(crate) fn group(&self, age: u8) -> (usize, String) {
+        let index = self.starts.partition_point(|&start| start <= age);
+        let label = match (index.checked_sub(1), self.starts.get(index)) {
+            (None, Some(end)) => format!("under {end}"),
+            (Some(start), Some(end)) => format!("{}-{}", self.starts[start], end - 1),
+            (Some(start), None) => format!("{} and over", self.starts[start]),
+            (None, None) => "all ages".to_string(),
+        };
+        (index, label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_groups() {
+        let groups = "20,30,40".parse::<AgeGroups>().unwrap();
+        let labels = [5, 19, 20, 29, 39, 40, 85].map(|age| groups.group(age).1);
+        assert_eq!(
+            labels,
+            [
+                "under 20",
+                "under 20",
+                "20-29",
+                "20-29",
+                "30-39",
+                "40 and over",
+                "40 and over"
+            ]
+        );
+        assert_eq!(AgeGroups::default().group(45), (3, "40-49".to_string()));
+        assert!("30,20".parse::<AgeGroups>().is_err());
+        assert!("0,20".parse::<AgeGroups>().is_err());
+        assert!("20,thirty".parse::<AgeGroups>().is_err());
+    }
+}
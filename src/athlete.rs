@@ -0,0 +1,488 @@
+// Everything one runner has finished, found by reading every results
+// file under some directories (by default, all of assets), so that the
+// assets tree doubles as a personal results database.  Names are
+// compared after aliases are applied and without regard to case, since
+// some timing companies SHOUT.
+//
+// Each appearance is marked PB if it's the runner's fastest at that
+// race, among two or more, and has the difference from their previous
+// appearance at it.  A race is known across years by its name without
+// the year (or, for files named by year, e.g., mt_taylor_50k/2019.json,
+// by its directory), along with the event and the course, if there is
+// one.  The same result can be in more than one file, e.g., a race
+// that's in a series directory as well as in its own, so an appearance
+// at the same race in the same year with the same time as an earlier
+// one is left out.
+//
+// Most results only list finishers, but UltraSignup's also list who
+// didn't finish, didn't start or was disqualified, so those appearances
+// are listed too, with their status and without a time or place.
+
+use {
+    crate::{
+        Duration, Finisher, Loader, MaleOrFemale, RaceResults, Status, names,
+        output::{Output, Times, optional_seconds, ranks, write_json, write_table},
+        streaks::year_from,
+        visible_entries,
+    },
+    anyhow::{Result, bail},
+    serde::{Serialize, Serializer},
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Serialize)]
+struct Appearance {
+    race: String,
+    event: Option<String>,
+    path: PathBuf,
+    year: Option<u16>,
+    /// None for a finish, which is written as "finished"
+    #[serde(serialize_with = "serialize_status")]
+    status: Option<Status>,
+    /// None if the runner didn't finish
+    #[serde(serialize_with = "optional_seconds")]
+    time: Option<Duration>,
+    /// overall place in the results file, if the runner finished, and
+    /// how many finished
+    place: Option<usize>,
+    finishers: usize,
+    /// place among the finishers of the same sex, and how many there were
+    sex: Option<MaleOrFemale>,
+    sex_place: Option<usize>,
+    sex_finishers: Option<usize>,
+    age: Option<u8>,
+    pb: bool,
+    /// seconds faster (negative) or slower than the previous appearance
+    /// at the same race
+    #[serde(serialize_with = "optional_signed_seconds")]
+    delta: Option<(bool, Duration)>,
+    #[serde(skip)]
+    key: (String, Option<String>, Option<String>),
+}
+
+fn serialize_status<S: Serializer>(status: &Option<Status>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&status_label(*status))
+}
+
+fn optional_signed_seconds<S: Serializer>(
+    delta: &Option<(bool, Duration)>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match delta {
+        None => s.serialize_none(),
+        Some((faster, duration)) => {
+            let secs = f64::from(*duration);
+            s.serialize_f64(if *faster { -secs } else { secs })
+        }
+    }
+}
+
+pub(crate) fn summarize(
+    name: &str,
+    paths: &[PathBuf],
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let wanted = key(name);
+    let mut files = vec![];
+    for path in paths {
+        results_files(path, &mut files)?;
+    }
+    let found = crate::load_in_order(files, |path| {
+        let race = loader.all_results(&path)?;
+        let appearances = appearances(&race, &wanted);
+        Ok((!appearances.is_empty()).then_some((race, appearances)))
+    })?;
+
+    let mut rows = vec![];
+    for (race, appearances) in found.into_iter().flatten() {
+        loader.check(&race)?;
+        rows.extend(appearances);
+    }
+    if rows.is_empty() {
+        bail!("Found no results for {name}");
+    }
+    rows.sort_by(|a1, a2| (a1.year, &a1.race, &a1.path).cmp(&(a2.year, &a2.race, &a2.path)));
+    dedup(&mut rows);
+    mark(&mut rows);
+
+    match output {
+        Output::Text => {
+            let race_width = rows.iter().map(|row| label(row).len()).max().unwrap_or(0);
+            for row in &rows {
+                println!(
+                    "{:4} {:race_width$} {:>10} {:>9} {:>9} {:>3} {:2} {}",
+                    row.year.map(|year| year.to_string()).unwrap_or_default(),
+                    label(row),
+                    match (row.status, row.time) {
+                        (Some(status), _) => status.to_string(),
+                        (None, Some(time)) => format!("{time:.1}"),
+                        (None, None) => String::new(),
+                    },
+                    match row.place {
+                        Some(place) => format!("{place}/{}", row.finishers),
+                        None => format!("-/{}", row.finishers),
+                    },
+                    match (row.sex, row.sex_place, row.sex_finishers) {
+                        (Some(sex), Some(place), Some(finishers)) =>
+                            format!("{sex} {place}/{finishers}"),
+                        _ => String::new(),
+                    },
+                    row.age.map(|age| age.to_string()).unwrap_or_default(),
+                    if row.pb { "PB" } else { "" },
+                    row.delta
+                        .map(|(faster, delta)| format!("{}{delta:.1}", sign(faster)))
+                        .unwrap_or_default(),
+                );
+            }
+        }
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            [
+                "year",
+                "race",
+                "event",
+                "status",
+                "time",
+                "place",
+                "finishers",
+                "sex",
+                "sex place",
+                "sex finishers",
+                "age",
+                "pb",
+                "delta",
+                "path",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows.iter().map(|row| {
+                let optional = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_default();
+                vec![
+                    row.year.map(|year| year.to_string()).unwrap_or_default(),
+                    row.race.clone(),
+                    row.event.clone().unwrap_or_default(),
+                    status_label(row.status),
+                    row.time.map(|time| times.format(time)).unwrap_or_default(),
+                    optional(row.place),
+                    row.finishers.to_string(),
+                    row.sex.map(|sex| sex.to_string()).unwrap_or_default(),
+                    optional(row.sex_place),
+                    optional(row.sex_finishers),
+                    row.age.map(|age| age.to_string()).unwrap_or_default(),
+                    if row.pb { "PB" } else { "" }.to_string(),
+                    row.delta
+                        .map(|(faster, delta)| format!("{}{}", sign(faster), times.format(delta)))
+                        .unwrap_or_default(),
+                    row.path.display().to_string(),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+fn status_label(status: Option<Status>) -> String {
+    status.map_or_else(|| "finished".to_string(), |status| status.to_string())
+}
+
+fn sign(faster: bool) -> char {
+    if faster { '-' } else { '+' }
+}
+
+fn label(row: &Appearance) -> Cow<'_, str> {
+    match &row.event {
+        None => Cow::from(&row.race),
+        Some(event) => Cow::from(format!("{} ({event})", row.race)),
+    }
+}
+
+// A name as it's compared: with aliases applied and in lower case.
+fn key(name: &str) -> String {
+    names::canonical(Cow::from(name.trim())).to_lowercase()
+}
+
+// Every results file at or under path, leaving out hidden files and
+// sidecars (and series.toml), in name order.
+fn results_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = visible_entries(path, false)?;
+    entries.sort();
+    files.extend(entries);
+    let mut directories = visible_entries(path, true)?;
+    directories.sort();
+    for directory in directories {
+        results_files(&directory, files)?;
+    }
+    Ok(())
+}
+
+// The runner's appearances in one results file, usually zero or one.
+fn appearances(race: &RaceResults, wanted: &str) -> Vec<Appearance> {
+    let mine = race
+        .finishers
+        .iter()
+        .filter(|finisher| key(&finisher.name) == wanted)
+        .collect::<Vec<_>>();
+    let not_finished = race
+        .non_finishers
+        .iter()
+        .filter(|non_finisher| key(&non_finisher.name) == wanted)
+        .collect::<Vec<_>>();
+    if mine.is_empty() && not_finished.is_empty() {
+        return vec![];
+    }
+    let name = race.name();
+    let year = race
+        .details
+        .year()
+        .or_else(|| race.path.ancestors().find_map(year_from));
+    // Files named by year, e.g., mt_taylor_50k/2019.json, go by their
+    // directory.
+    let (name, base) = match without_year(&name) {
+        Some(base) => (name, base),
+        None => {
+            let directory = race_directory(&race.path);
+            (directory.clone(), directory)
+        }
+    };
+    let mut appearances = mine
+        .into_iter()
+        .map(|finisher| {
+            let (place, finishers) = place_in(race, finisher, |_| true);
+            let (sex_place, sex_finishers) = match finisher.morf {
+                None => (None, None),
+                Some(morf) => {
+                    let (place, finishers) = place_in(race, finisher, |f| f.morf == Some(morf));
+                    (Some(place), Some(finishers))
+                }
+            };
+            Appearance {
+                race: name.clone(),
+                event: finisher.event.clone(),
+                path: race.path.clone(),
+                year,
+                status: None,
+                time: Some(finisher.time),
+                place: Some(place),
+                finishers,
+                sex: finisher.morf,
+                sex_place,
+                sex_finishers,
+                age: finisher.age,
+                pb: false,
+                delta: None,
+                key: (
+                    base.clone(),
+                    finisher.event.clone(),
+                    race.details.course.clone(),
+                ),
+            }
+        })
+        .collect::<Vec<_>>();
+    appearances.extend(not_finished.into_iter().map(|non_finisher| Appearance {
+        race: name.clone(),
+        event: None,
+        path: race.path.clone(),
+        year,
+        status: Some(non_finisher.status),
+        time: None,
+        place: None,
+        finishers: race.finishers.len(),
+        sex: None,
+        sex_place: None,
+        sex_finishers: None,
+        age: None,
+        pb: false,
+        delta: None,
+        key: (base.clone(), None, race.details.course.clone()),
+    }));
+    appearances
+}
+
+// The finisher's place among the finishers that count, with ties
+// sharing a place, and how many of them there are.
+fn place_in(
+    race: &RaceResults,
+    finisher: &Finisher,
+    counts: impl Fn(&Finisher) -> bool,
+) -> (usize, usize) {
+    let mut times = race
+        .finishers
+        .iter()
+        .filter(|f| counts(f) && f.event == finisher.event)
+        .map(|f| f.time)
+        .collect::<Vec<_>>();
+    times.sort();
+    let ranks = ranks(&times, |&time| time);
+    let index = times.partition_point(|&time| time < finisher.time);
+    (ranks[index], times.len())
+}
+
+// The race's name without its year, e.g., Cedro Peak 50k for Cedro
+// Peak 50k 2023, unless that leaves nothing.
+fn without_year(name: &str) -> Option<String> {
+    let words = name
+        .split(['-', '_', ' '])
+        .filter(|word| !word.is_empty() && year_from(Path::new(word)).is_none())
+        .collect::<Vec<_>>();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+// The nearest directory whose name isn't a year.
+fn race_directory(path: &Path) -> String {
+    path.ancestors()
+        .skip(1)
+        .filter_map(|ancestor| ancestor.file_name()?.to_str())
+        .find(|name| year_from(Path::new(name)).is_none())
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Leaves out the appearances that are the same result as an earlier one,
+// found in another file.  Two races in a year can have the same time,
+// e.g., the same runner's 5K at two different races, so the race is
+// part of what has to match.
+fn dedup(rows: &mut Vec<Appearance>) {
+    let mut seen = vec![];
+    rows.retain(|row| {
+        let result = (row.year, row.time, row.key.clone());
+        let duplicate = seen.contains(&result);
+        seen.push(result);
+        !duplicate
+    });
+}
+
+// Marks each race's fastest appearance, when there's more than one, and
+// the difference from the previous appearance at the same race.  The
+// rows are in year order.  Only finishes count.
+fn mark(rows: &mut [Appearance]) {
+    let mut by_race = HashMap::<_, Vec<(usize, Duration)>>::new();
+    for (i, row) in rows.iter().enumerate() {
+        if let Some(time) = row.time {
+            by_race.entry(row.key.clone()).or_default().push((i, time));
+        }
+    }
+    for finishes in by_race.values() {
+        if finishes.len() < 2 {
+            continue;
+        }
+        let (fastest, _) = *finishes.iter().min_by_key(|(_, time)| time).unwrap();
+        rows[fastest].pb = true;
+        for pair in finishes.windows(2) {
+            let ((_, previous), (current_index, current)) = (pair[0], pair[1]);
+            let faster = current < previous;
+            let delta = current.abs_diff(*previous);
+            rows[current_index].delta =
+                Some((faster, Duration::new(delta.as_secs(), delta.subsec_nanos())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_year() {
+        assert_eq!(
+            without_year("Cedro Peak 50k 2023").as_deref(),
+            Some("Cedro Peak 50k")
+        );
+        assert_eq!(
+            without_year("bmdm-2026-solo-male-heavy").as_deref(),
+            Some("bmdm solo male heavy")
+        );
+        assert_eq!(without_year("2019"), None);
+        assert_eq!(
+            race_directory(Path::new("assets/mt_taylor_50k/2019.json")),
+            "mt_taylor_50k"
+        );
+        assert_eq!(
+            race_directory(Path::new("assets/quad/2011/2011.html")),
+            "quad"
+        );
+    }
+
+    fn appearance(race: &str, year: u16, secs: u64) -> Appearance {
+        Appearance {
+            race: format!("{race} {year}"),
+            event: None,
+            path: PathBuf::new(),
+            year: Some(year),
+            status: None,
+            time: Some(Duration::new(secs, 0)),
+            place: Some(1),
+            finishers: 1,
+            sex: None,
+            sex_place: None,
+            sex_finishers: None,
+            age: None,
+            pb: false,
+            delta: None,
+            key: (race.to_string(), None, None),
+        }
+    }
+
+    #[test]
+    fn test_mark() {
+        let mut did_not_finish = appearance("Cedro Peak 50k", 2023, 0);
+        did_not_finish.status = Some(Status::DidNotFinish);
+        did_not_finish.time = None;
+        let mut rows = [
+            appearance("Cedro Peak 50k", 2022, 20_000),
+            did_not_finish,
+            appearance("Cedro Peak 50k", 2023, 19_000),
+            appearance("Cedro Peak 50k", 2024, 19_500),
+        ];
+        mark(&mut rows);
+        assert_eq!(
+            rows.map(|row| (row.pb, row.delta)),
+            [
+                (false, None),
+                (false, None),
+                (true, Some((true, Duration::new(1000, 0)))),
+                (false, Some((false, Duration::new(500, 0)))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut rows = vec![
+            appearance("Turkey Trot", 2024, 1500),
+            appearance("Turkey Trot", 2024, 1500),
+            appearance("Grinch Lunch", 2024, 1500),
+            appearance("Turkey Trot", 2025, 1500),
+        ];
+        dedup(&mut rows);
+        assert_eq!(
+            rows.iter().map(|row| &row.race).collect::<Vec<_>>(),
+            ["Turkey Trot 2024", "Grinch Lunch 2024", "Turkey Trot 2025"]
+        );
+    }
+
+    #[test]
+    fn test_non_finishers() {
+        let race = crate::load_results("assets/jj100/2019.json").unwrap();
+        for (name, status) in [
+            ("Bob Alexander", Status::DidNotFinish),
+            ("Nikolai Spence", Status::DidNotStart),
+        ] {
+            let appearances = appearances(&race, &key(name));
+            assert_eq!(appearances.len(), 1);
+            assert_eq!(appearances[0].status, Some(status));
+            assert_eq!((appearances[0].time, appearances[0].place), (None, None));
+        }
+        assert_eq!(appearances(&race, &key("Patrick Reagan"))[0].status, None);
+    }
+}
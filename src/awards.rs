@@ -0,0 +1,259 @@
+// Award lists for one race, the way race directors hand them out: the
+// fastest few of each sex overall, then the fastest few of each sex in
+// each age group.  Overall winners aren't also given an age group
+// award (which goes to the next finisher in their group instead)
+// unless --double is given.  Finishers whose results don't list a sex
+// can't win anything, and neither can finishers without an age win an
+// age group award.
+
+use {
+    crate::{
+        Duration, Finisher, Loader, MaleOrFemale, Source,
+        age_groups::AgeGroups,
+        names,
+        output::{Output, Times, ranks, seconds, write_json, write_table},
+    },
+    anyhow::Result,
+    serde::Serialize,
+    std::borrow::Cow,
+};
+
+#[derive(Debug)]
+pub(crate) struct Rules {
+    pub(crate) age_groups: AgeGroups,
+    /// how many of each sex get an overall award
+    pub(crate) overall: usize,
+    /// how many of each sex get an award in each age group
+    pub(crate) top: usize,
+    /// whether overall winners can also win their age group
+    pub(crate) double: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Award {
+    sex: MaleOrFemale,
+    /// overall, or the age group, e.g., 40-49
+    award: String,
+    place: usize,
+    name: String,
+    age: Option<u8>,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+}
+
+const SEXES: [MaleOrFemale; 3] = [
+    MaleOrFemale::Male,
+    MaleOrFemale::Female,
+    MaleOrFemale::NonBinary,
+];
+
+pub(crate) fn summarize(
+    source: &Source,
+    rules: &Rules,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let race = loader.source(source)?;
+    loader.check(&race)?;
+    let unknown = race
+        .finishers
+        .iter()
+        .filter(|finisher| finisher.morf.is_none())
+        .count();
+    if unknown > 0 {
+        eprintln!("{unknown} finisher(s) in {source} have no sex and can't win an award");
+    }
+    let awards = awards(&race.finishers, rules);
+
+    match output {
+        Output::Text => {
+            let name_width = awards
+                .iter()
+                .map(|award| award.name.len())
+                .max()
+                .unwrap_or(0);
+            let mut previous: Option<(MaleOrFemale, &str)> = None;
+            for award in &awards {
+                if previous != Some((award.sex, &award.award)) {
+                    if previous.is_some() {
+                        println!();
+                    }
+                    println!("{} {}", award.sex, award.award);
+                    previous = Some((award.sex, &award.award));
+                }
+                println!(
+                    "{:>4} {:name_width$} {:>3} {:>10.1}",
+                    award.place,
+                    award.name,
+                    award.age.map(|age| age.to_string()).unwrap_or_default(),
+                    award.time
+                );
+            }
+        }
+        Output::Json => write_json(&awards)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            ["sex", "award", "place", "name", "age", "time"]
+                .map(String::from)
+                .to_vec(),
+            awards.iter().map(|award| {
+                vec![
+                    award.sex.to_string(),
+                    award.award.clone(),
+                    award.place.to_string(),
+                    award.name.clone(),
+                    award.age.map(|age| age.to_string()).unwrap_or_default(),
+                    times.format(award.time),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+// Every award, by sex, then overall before the age groups (youngest
+// first), then place.  Finishers who tie share a place, so a tie for
+// the last award gives out an extra one.
+fn awards(finishers: &[Finisher], rules: &Rules) -> Vec<Award> {
+    let mut finishers = finishers.iter().collect::<Vec<_>>();
+    finishers.sort_by_key(|finisher| finisher.time);
+    let mut awards = vec![];
+    for sex in SEXES {
+        let of_sex = finishers
+            .iter()
+            .copied()
+            .filter(|finisher| finisher.morf == Some(sex))
+            .collect::<Vec<_>>();
+        let overall = winners(&of_sex, rules.overall);
+        awards.extend(
+            overall
+                .iter()
+                .map(|&(place, finisher)| award(sex, "overall", place, finisher)),
+        );
+
+        let mut groups = of_sex
+            .iter()
+            .filter_map(|finisher| Some(rules.age_groups.group(finisher.age?)))
+            .collect::<Vec<_>>();
+        groups.sort();
+        groups.dedup();
+        for (index, label) in groups {
+            let in_group = of_sex
+                .iter()
+                .copied()
+                .filter(|finisher| {
+                    finisher
+                        .age
+                        .is_some_and(|age| rules.age_groups.group(age).0 == index)
+                        && (rules.double
+                            || !overall
+                                .iter()
+                                .any(|&(_, winner)| std::ptr::eq(winner, *finisher)))
+                })
+                .collect::<Vec<_>>();
+            awards.extend(
+                winners(&in_group, rules.top)
+                    .into_iter()
+                    .map(|(place, finisher)| award(sex, &label, place, finisher)),
+            );
+        }
+    }
+    awards
+}
+
+// The finishers, who are in time order, who place in the top places.
+fn winners<'a>(finishers: &[&'a Finisher], places: usize) -> Vec<(usize, &'a Finisher)> {
+    let ranks = ranks(finishers, |finisher| finisher.time);
+    ranks
+        .into_iter()
+        .zip(finishers.iter().copied())
+        .take_while(|&(place, _)| place <= places)
+        .collect()
+}
+
+fn award(sex: MaleOrFemale, award: &str, place: usize, finisher: &Finisher) -> Award {
+    Award {
+        sex,
+        award: award.to_string(),
+        place,
+        name: names::canonical(Cow::from(&finisher.name)).into_owned(),
+        age: finisher.age,
+        time: finisher.time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, MaleOrFemale::*};
+
+    #[test]
+    fn test_awards() {
+        let finishers = [
+            ("A", 100, Male, Some(45)),
+            ("B", 110, Male, Some(42)),
+            ("C", 120, Male, Some(48)),
+            ("D", 130, Male, Some(41)),
+            ("E", 130, Male, Some(44)),
+            ("F", 140, Male, None),
+            ("G", 150, Male, Some(25)),
+            ("H", 125, Female, Some(33)),
+        ]
+        .map(|(name, secs, morf, age)| Finisher {
+            name: name.to_string(),
+            time: Duration::new(secs, 0),
+            morf: Some(morf),
+            event: None,
+            pace: None,
+            age,
+        });
+        let listed = |rules: &Rules| {
+            awards(&finishers, rules)
+                .into_iter()
+                .map(|award| {
+                    format!(
+                        "{} {} {} {}",
+                        award.sex, award.award, award.place, award.name
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut rules = Rules {
+            age_groups: AgeGroups::default(),
+            overall: 1,
+            top: 2,
+            double: false,
+        };
+        assert_eq!(
+            listed(&rules),
+            [
+                "M overall 1 A",
+                "M 20-29 1 G",
+                "M 40-49 1 B",
+                "M 40-49 2 C",
+                "F overall 1 H",
+            ]
+        );
+
+        rules.double = true;
+        rules.top = 3;
+        assert_eq!(
+            listed(&rules),
+            [
+                "M overall 1 A",
+                "M 20-29 1 G",
+                "M 40-49 1 A",
+                "M 40-49 2 B",
+                "M 40-49 3 C",
+                "F overall 1 H",
+                "F 30-39 1 H",
+            ]
+        );
+
+        rules.double = false;
+        assert_eq!(
+            listed(&rules)[2..6],
+            ["M 40-49 1 B", "M 40-49 2 C", "M 40-49 3 D", "M 40-49 3 E",]
+        );
+    }
+}
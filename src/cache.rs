@@ -0,0 +1,247 @@
+// Most of what we run on are historical results that never change, so
+// decoding the MHTML and parsing it again on every run is wasted
+// time.  The cache stores what the parsers found, keyed by a hash of
+// the file's contents, so renaming or copying a file doesn't matter,
+// but editing it does.  Entries live in a directory per PARSER_VERSION,
+// so that old entries are simply ignored after a parser changes and
+// `runs cache prune` can remove them.
+//
+// The cache is best effort: an entry that can't be read is reparsed
+// and an entry that can't be written is skipped.
+
+use {
+    crate::{
+        Duration, Finisher, MaleOrFemale, NonFinisher, RaceDetails, RaceResults, Warning, details,
+        parse,
+    },
+    anyhow::{Result, anyhow},
+    serde::{Deserialize, Serialize},
+    sha2::{Digest, Sha256},
+    std::{
+        env, fs, io,
+        path::{Path, PathBuf},
+        process, thread,
+    },
+};
+
+// Bump this whenever a change to a parser (or to names_and_times) could
+// change what's found in a file that has already been cached.
+const PARSER_VERSION: u32 = 11;
+
+pub struct Cache {
+    directory: PathBuf,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    finishers: Vec<CachedFinisher>,
+    warnings: Vec<Warning>,
+    details: RaceDetails,
+    did_not_finish: Option<usize>,
+    non_finishers: Vec<NonFinisher>,
+}
+
+// Durations are stored exactly, rather than as fractional seconds.
+#[derive(Deserialize, Serialize)]
+struct CachedFinisher {
+    name: String,
+    secs: u64,
+    nanos: u32,
+    morf: Option<MaleOrFemale>,
+    event: Option<String>,
+    pace: Option<(u64, u32)>,
+    age: Option<u8>,
+}
+
+impl Cache {
+    /// The cache in $RUNS_CACHE_DIR, $XDG_CACHE_HOME/runs or
+    /// ~/.cache/runs, whichever is found first.
+    pub fn open() -> Result<Self> {
+        let directory = match env::var_os("RUNS_CACHE_DIR") {
+            Some(directory) => PathBuf::from(directory),
+            None => env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+                .ok_or_else(|| anyhow!("Can't find a cache directory; try setting RUNS_CACHE_DIR"))?
+                .join("runs"),
+        };
+        Ok(Self::new(directory))
+    }
+
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Like load_results, but uses the cached results if the file has
+    /// been parsed before.  Sidecars aren't cached, since they're cheap
+    /// to read and more likely to be edited than results.
+    pub fn load_results(&self, path: impl AsRef<Path>) -> Result<RaceResults> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let entry = self.entry_path(&bytes);
+        let race = match Self::read(&entry, path) {
+            Some(race) => race,
+            None => {
+                let race = parse(path, &bytes);
+                // Failing to write the cache shouldn't fail the run.
+                let _ = Self::write(&entry, &race);
+                race
+            }
+        };
+        details::with_sidecar(race)
+    }
+
+    /// Removes the entries from other parser versions, or every entry
+    /// if all is true, and returns how many files were removed.  Only
+    /// version directories (v1, v2 and so on) are touched, since
+    /// $RUNS_CACHE_DIR may hold other things.
+    pub fn prune(&self, all: bool) -> Result<usize> {
+        let current = self.version_directory();
+        let mut removed = 0;
+        let entries = match fs::read_dir(&self.directory) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            entries => entries?,
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() && is_version(&path) && (all || path != current) {
+                removed += fs::read_dir(&path)?.count();
+                fs::remove_dir_all(&path)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn version_directory(&self) -> PathBuf {
+        self.directory.join(format!("v{PARSER_VERSION}"))
+    }
+
+    fn entry_path(&self, bytes: &[u8]) -> PathBuf {
+        self.version_directory()
+            .join(format!("{:x}.json", Sha256::digest(bytes)))
+    }
+
+    fn read(entry: &Path, path: &Path) -> Option<RaceResults> {
+        let Entry {
+            finishers,
+            warnings,
+            details,
+            did_not_finish,
+            non_finishers,
+        } = serde_json::from_slice(&fs::read(entry).ok()?).ok()?;
+        Some(RaceResults {
+            path: path.to_path_buf(),
+            finishers: finishers
+                .into_iter()
+                .map(|finisher| Finisher {
+                    name: finisher.name,
+                    time: Duration::new(finisher.secs, finisher.nanos),
+                    morf: finisher.morf,
+                    event: finisher.event,
+                    pace: finisher
+                        .pace
+                        .map(|(secs, nanos)| Duration::new(secs, nanos)),
+                    age: finisher.age,
+                })
+                .collect(),
+            warnings,
+            details,
+            did_not_finish,
+            non_finishers,
+        })
+    }
+
+    // Written to a temporary file and then renamed, so that a reader
+    // (possibly another thread loading a copy of the same file) never
+    // sees a partial entry.
+    fn write(entry: &Path, race: &RaceResults) -> Result<()> {
+        let json = serde_json::to_vec(&Entry {
+            finishers: race
+                .finishers
+                .iter()
+                .map(|finisher| CachedFinisher {
+                    name: finisher.name.clone(),
+                    secs: finisher.time.as_secs(),
+                    nanos: finisher.time.subsec_nanos(),
+                    morf: finisher.morf,
+                    event: finisher.event.clone(),
+                    pace: finisher
+                        .pace
+                        .map(|pace| (pace.as_secs(), pace.subsec_nanos())),
+                    age: finisher.age,
+                })
+                .collect(),
+            warnings: race.warnings.clone(),
+            details: race.details.clone(),
+            did_not_finish: race.did_not_finish,
+            non_finishers: race.non_finishers.clone(),
+        })?;
+        fs::create_dir_all(entry.parent().unwrap())?;
+        let temporary = entry.with_extension(format!(
+            "{}.{:?}.tmp",
+            process::id(),
+            thread::current().id()
+        ));
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, entry)?;
+        Ok(())
+    }
+}
+
+// Whether path is named like a version directory, v and digits.
+fn is_version(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix('v'))
+        .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let directory = env::temp_dir().join(format!("runs-cache-test-{}", process::id()));
+        let cache = Cache::new(&directory);
+        let path = Path::new("assets/mt_taylor_50k/2019.json");
+
+        let parsed = crate::load_results(path).unwrap();
+        let first = cache.load_results(path).unwrap();
+        let second = cache.load_results(path).unwrap();
+        for race in [first, second] {
+            assert_eq!(race.path, parsed.path);
+            assert_eq!(race.finishers.len(), parsed.finishers.len());
+            assert!(
+                race.finishers
+                    .iter()
+                    .zip(&parsed.finishers)
+                    .all(|(f1, f2)| f1.name == f2.name
+                        && f1.time == f2.time
+                        && f1.morf == f2.morf
+                        && f1.event == f2.event
+                        && f1.pace == f2.pace
+                        && f1.age == f2.age)
+            );
+        }
+
+        fs::create_dir_all(directory.join("v0")).unwrap();
+        fs::write(directory.join("v0").join("old.json"), "{}").unwrap();
+        for unrelated in ["notes", "v", "v2-backup"] {
+            fs::create_dir_all(directory.join(unrelated)).unwrap();
+            fs::write(directory.join(unrelated).join("keep.txt"), "").unwrap();
+        }
+        assert_eq!(cache.prune(false).unwrap(), 1);
+        assert_eq!(cache.prune(true).unwrap(), 1);
+        for unrelated in ["notes", "v", "v2-backup"] {
+            assert!(directory.join(unrelated).join("keep.txt").exists());
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
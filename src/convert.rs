@@ -0,0 +1,45 @@
+// Saves what the parsers found in a results file in runs' own format
+// (see parser/canonical.rs), as JSON or CSV depending on the output
+// file's extension.  The race's details are whatever was given on the
+// command line, falling back to what the input already has (from its
+// sidecar, or when converting a file that's already in runs' format
+// from JSON to CSV).  How many didn't finish is saved, too, for results
+// that say.
+
+use {
+    crate::{Loader, RaceDetails, Source, parser::canonical},
+    anyhow::{Context, Result, bail},
+    std::{
+        fs::File,
+        io::{BufWriter, Write},
+        path::Path,
+    },
+};
+
+pub(crate) fn convert(
+    input: &Source,
+    out: &Path,
+    details: RaceDetails,
+    loader: &Loader,
+) -> Result<()> {
+    let write = match out.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json") => canonical::write_json,
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => canonical::write_csv,
+        _ => bail!(
+            "Don't know how to write {}; it should end in .json or .csv",
+            out.display()
+        ),
+    };
+    let race = loader.source(input)?;
+    loader.check(&race)?;
+    if race.finishers.is_empty() {
+        bail!("No finishers found in {input}");
+    }
+    let details = details.or(race.details);
+    let mut writer = BufWriter::new(
+        File::create(out).with_context(|| format!("Can't create {}", out.display()))?,
+    );
+    write(&mut writer, &details, race.did_not_finish, &race.finishers)?;
+    writer.flush()?;
+    Ok(())
+}
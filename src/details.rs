@@ -0,0 +1,321 @@
+// A results file only has the finishers, so the race itself is
+// identified by its path: the file name is the race's name and the year,
+// distance and category are implied by the directories it's in.  A
+// results file can have a sidecar, the same path with .toml appended
+// (e.g., 2025_v_day_run.html.toml), that says what the race is, e.g.,
+//
+//     name = "V Day Run"
+//     date = "2025-02-15"
+//     distance = 5
+//     units = "km"        # what a distance without units is in
+//     surface = "road"    # or "trail"
+//     course = "2019"     # for races whose course has changed
+//
+// Everything is optional.  A series.toml entry (see manifest.rs) wins
+// over a sidecar, which wins over what a file in runs' own format (see
+// parser/canonical.rs) says about itself.
+
+use {
+    crate::RaceResults,
+    anyhow::{Context, Result},
+    clap::ValueEnum,
+    serde::{Deserialize, Deserializer, Serialize},
+    std::{
+        ffi::OsString,
+        fmt::{self, Display, Formatter},
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+const EXTENSION: &str = "toml";
+
+/// What's known about a race besides its results, which is nothing
+/// unless it has a sidecar, is listed in a series.toml or was saved by
+/// runs convert.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RaceDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// e.g., 2025-02-15
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// e.g., 5K, 13.1 mi, marathon or just a number that's in units
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "number_or_string"
+    )]
+    pub distance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface: Option<Surface>,
+    /// which version of the course was run, for races whose course has
+    /// changed, e.g., 2019
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub course: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// kilometers
+    #[serde(alias = "k", alias = "kilometers")]
+    Km,
+    /// miles
+    #[default]
+    #[serde(alias = "miles")]
+    Mi,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Surface {
+    Road,
+    Trail,
+}
+
+impl Display for Units {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Units::Km => "km",
+            Units::Mi => "mi",
+        })
+    }
+}
+
+impl Display for Surface {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Surface::Road => "road",
+            Surface::Trail => "trail",
+        })
+    }
+}
+
+impl RaceDetails {
+    /// Each detail that self doesn't have is taken from other.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            name: self.name.or(other.name),
+            date: self.date.or(other.date),
+            distance: self.distance.or(other.distance),
+            units: self.units.or(other.units),
+            surface: self.surface.or(other.surface),
+            course: self.course.or(other.course),
+        }
+    }
+
+    /// The distance in meters, if it's known.  Besides a number and
+    /// units (5K, 10 km, 13.1 mi, 50M, which is miles, as in race
+    /// names), the distance can be marathon or half marathon.  A
+    /// number without units is in units.  A distance of 0 isn't known,
+    /// since nothing can be divided by it.
+    pub fn meters(&self) -> Option<f64> {
+        meters(self.distance.as_deref()?, self.units)
+    }
+
+    /// The year of the date, if there is one.
+    pub fn year(&self) -> Option<u16> {
+        self.date.as_deref()?.get(..4)?.parse().ok()
+    }
+}
+
+pub(crate) const METERS_PER_MILE: f64 = 1609.344;
+const MARATHON: f64 = 42_195.0;
+
+pub(crate) fn meters(distance: &str, units: Option<Units>) -> Option<f64> {
+    let distance = distance.trim().to_lowercase();
+    match distance.replace(['-', ' '], "").as_str() {
+        "marathon" => return Some(MARATHON),
+        "halfmarathon" | "half" => return Some(MARATHON / 2.0),
+        _ => (),
+    }
+    let number_end = distance
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(distance.len());
+    let number = distance[..number_end]
+        .parse::<f64>()
+        .ok()
+        .filter(|&number| number > 0.0)?;
+    let units = match distance[number_end..].trim() {
+        "" => units?,
+        "k" | "km" | "kilometer" | "kilometers" => Units::Km,
+        "m" | "mi" | "mile" | "miles" | "miler" => Units::Mi,
+        _ => return None,
+    };
+    Some(
+        number
+            * match units {
+                Units::Km => 1000.0,
+                Units::Mi => METERS_PER_MILE,
+            },
+    )
+}
+
+// The distance that a race's name (or a category's) gives away, e.g.,
+// Forever Young 6 Miler, Cedro Peak 50k or Duke City Marathon Half,
+// for races without a distance in their details.  A year, or any other
+// number without units, isn't a distance.
+pub(crate) fn meters_in_name(name: &str) -> Option<f64> {
+    let words = name.split_whitespace().collect::<Vec<_>>();
+    for (i, word) in words.iter().enumerate() {
+        if !word.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        if let Some(meters) = meters(word, None) {
+            return Some(meters);
+        }
+        if let Some(next) = words.get(i + 1)
+            && let Some(meters) = meters(&format!("{word} {next}"), None)
+        {
+            return Some(meters);
+        }
+    }
+    let name = name.to_lowercase();
+    if name.contains("half") {
+        Some(MARATHON / 2.0)
+    } else if name.contains("marathon") {
+        Some(MARATHON)
+    } else {
+        None
+    }
+}
+
+// So that distance = 5 works as well as distance = "5K".
+pub(crate) fn number_or_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Distance {
+        Number(f64),
+        Text(String),
+    }
+
+    Ok(
+        Option::<Distance>::deserialize(deserializer)?.map(|distance| match distance {
+            Distance::Number(number) => number.to_string(),
+            Distance::Text(text) => text,
+        }),
+    )
+}
+
+pub(crate) fn sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = OsString::from(path);
+    sidecar.push(".");
+    sidecar.push(EXTENSION);
+    PathBuf::from(sidecar)
+}
+
+// Sidecars, as well as series.toml, are never results files.
+pub(crate) fn is_sidecar(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == EXTENSION)
+}
+
+// The details in path's sidecar, or None if it doesn't have one.
+pub(crate) fn sidecar(path: &Path) -> Result<Option<RaceDetails>> {
+    let sidecar = sidecar_path(path);
+    let text = match fs::read_to_string(&sidecar) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        text => text?,
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .with_context(|| format!("Can't read {}", sidecar.display()))
+}
+
+// The race with what its sidecar says, if it has one.
+pub(crate) fn with_sidecar(mut race: RaceResults) -> Result<RaceResults> {
+    if let Some(details) = sidecar(&race.path)? {
+        race.details = details.or(race.details);
+    }
+    Ok(race)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meters(distance: &str, units: Option<Units>) -> Option<f64> {
+        RaceDetails {
+            distance: Some(distance.to_string()),
+            units,
+            ..RaceDetails::default()
+        }
+        .meters()
+    }
+
+    #[test]
+    fn test_meters() {
+        assert_eq!(meters("5K", None), Some(5000.0));
+        assert_eq!(meters("10 km", None), Some(10_000.0));
+        assert_eq!(meters("13.1 mi", None), Some(13.1 * METERS_PER_MILE));
+        assert_eq!(meters("50M", None), Some(50.0 * METERS_PER_MILE));
+        assert_eq!(meters("Half Marathon", None), Some(21_097.5));
+        assert_eq!(meters("5", Some(Units::Km)), Some(5000.0));
+        assert_eq!(meters("5", None), None);
+        assert_eq!(meters("5 furlongs", None), None);
+        assert_eq!(meters("", None), None);
+        for zero in ["0", "0K", "0 mi", "0.0 km"] {
+            assert_eq!(meters(zero, Some(Units::Km)), None);
+        }
+    }
+
+    #[test]
+    fn test_meters_in_name() {
+        for (name, meters) in [
+            ("Forever Young 6 Miler 2022", Some(6.0 * METERS_PER_MILE)),
+            ("Cedro Peak 50k 2023", Some(50_000.0)),
+            (
+                "Jemez Mountain Trail Run 50M 2024",
+                Some(50.0 * METERS_PER_MILE),
+            ),
+            ("Duke City Marathon Half 2022", Some(21_097.5)),
+            ("Boston Bound Marathon Full 2022", Some(42_195.0)),
+            ("half-marathon", Some(21_097.5)),
+            ("10k", Some(10_000.0)),
+            ("Deadman Peaks Miler 2024", None),
+            ("long-trail", None),
+        ] {
+            assert_eq!(meters_in_name(name), meters, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_sidecar() {
+        let details: RaceDetails = toml::from_str(
+            r#"
+            name = "V Day Run"
+            date = "2025-02-15"
+            distance = 5
+            units = "km"
+            surface = "road"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(details.distance.as_deref(), Some("5"));
+        assert_eq!(details.meters(), Some(5000.0));
+        assert_eq!(details.surface, Some(Surface::Road));
+        assert_eq!(details.year(), Some(2025));
+        assert!(toml::from_str::<RaceDetails>("nmae = \"typo\"").is_err());
+
+        let other = RaceDetails {
+            name: Some("2025_v_day_run".to_string()),
+            course: Some("2019".to_string()),
+            ..RaceDetails::default()
+        };
+        let details = details.or(other);
+        assert_eq!(details.name.as_deref(), Some("V Day Run"));
+        assert_eq!(details.course.as_deref(), Some("2019"));
+
+        assert_eq!(
+            sidecar_path(Path::new("a/2025_v_day_run.html")),
+            Path::new("a/2025_v_day_run.html.toml")
+        );
+        assert!(is_sidecar(Path::new("a/2025_v_day_run.html.toml")));
+        assert!(!is_sidecar(Path::new("a/2025_v_day_run.html")));
+    }
+}
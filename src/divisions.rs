@@ -0,0 +1,260 @@
+// Some races post one results file per division rather than a single
+// overall page, e.g., the Bataan Memorial Death March's
+// bmdm-2026-solo-male-heavy.json, bmdm-2026-solo-female-military-heavy.json
+// and so on.  The division is whatever follows the year in the file
+// name, and the sex is implied by the division saying male or female.
+// A directory of one year's files is combined into a single race whose
+// finishers have their division as their event, so that everyone can
+// be ranked both overall and within their division (and --event can
+// pick out one division).  A directory of such years gives each
+// division's history.
+
+use {
+    crate::{
+        Duration, Loader, MaleOrFemale, RaceResults, combine_results, file_name, load_in_order,
+        output::{Output, Times, ranks, seconds, write_json, write_table},
+        streaks::year_from,
+        visible_entries,
+    },
+    anyhow::{Result, anyhow},
+    serde::Serialize,
+    std::{collections::HashMap, path::Path},
+};
+
+// The division and implied sex from a file name like
+// bmdm-2026-solo-female-military-heavy.json, i.e.,
+// ("solo-female-military-heavy", Some(Female)).
+pub(crate) fn division(path: &Path) -> Option<(String, Option<MaleOrFemale>)> {
+    let year = year_from(path)?.to_string();
+    let stem = path.file_stem()?.to_str()?;
+    let (_, division) = stem.rsplit_once(year.as_str())?;
+    let division = division.trim_start_matches(['-', '_', ' ']);
+    if division.is_empty() {
+        return None;
+    }
+    Some((division.to_string(), implied_sex(division)))
+}
+
+// The sex a division (or category, e.g., MALE 45-49) is for, if it says.
+pub(crate) fn implied_sex(division: &str) -> Option<MaleOrFemale> {
+    let words = division
+        .split(['-', '_', ' '])
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if words.iter().any(|word| word == "female" || word == "women") {
+        Some(MaleOrFemale::Female)
+    } else if words.iter().any(|word| word == "male" || word == "men") {
+        Some(MaleOrFemale::Male)
+    } else {
+        None
+    }
+}
+
+// Lists every finisher under the division, regardless of what the
+// results file itself says.
+pub(crate) fn label(race: &mut RaceResults, division: &str) {
+    for finisher in &mut race.finishers {
+        finisher.event = Some(division.to_string());
+    }
+}
+
+// One year's division files as a single race.
+fn load(directory: &Path, loader: &Loader) -> Result<RaceResults> {
+    let mut paths = visible_entries(directory, false)?;
+    paths.sort();
+    let divisions = paths
+        .iter()
+        .map(|path| division(path).ok_or_else(|| anyhow!("No division in {}", path.display())))
+        .collect::<Result<Vec<_>>>()?;
+    let races = load_in_order(&paths, |path| loader.all_results(path))?;
+    let mut parts = vec![];
+    for (mut race, (division, sex)) in races.into_iter().zip(divisions) {
+        loader.check(&race)?;
+        label(&mut race, &division);
+        parts.push((race, sex));
+    }
+    let mut race = combine_results(parts)
+        .ok_or_else(|| anyhow!("No division files in {}", directory.display()))?;
+    race.details.name = Some(file_name(directory));
+    loader.select(race)
+}
+
+#[derive(Serialize)]
+struct Ranking<'a> {
+    rank: usize,
+    division_rank: usize,
+    name: &'a str,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+    division: &'a str,
+    sex: Option<MaleOrFemale>,
+}
+
+pub(crate) fn rankings(
+    directory: &Path,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let race = load(directory, loader)?;
+    let mut finishers = race.finishers.iter().collect::<Vec<_>>();
+    finishers.sort_by_key(|finisher| finisher.time);
+    let overall = ranks(&finishers, |finisher| finisher.time);
+
+    // Standard competition ranking within each division, just like
+    // overall.
+    let mut previous: HashMap<&str, (usize, Duration, usize)> = HashMap::new();
+    let rows = finishers
+        .iter()
+        .zip(overall)
+        .map(|(finisher, rank)| {
+            let division = finisher.event.as_deref().unwrap_or_default();
+            let (count, time, division_rank) =
+                previous.entry(division).or_insert((0, finisher.time, 1));
+            *count += 1;
+            if *time != finisher.time {
+                *time = finisher.time;
+                *division_rank = *count;
+            }
+            Ranking {
+                rank,
+                division_rank: *division_rank,
+                name: &finisher.name,
+                time: finisher.time,
+                division,
+                sex: finisher.morf,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match output {
+        Output::Text => {
+            let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+            for row in &rows {
+                println!(
+                    "{:>4} {:>4} {:name_width$} {:>10.1} {}",
+                    row.rank, row.division_rank, row.name, row.time, row.division
+                );
+            }
+        }
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            ["rank", "division rank", "name", "time", "division", "sex"]
+                .map(String::from)
+                .to_vec(),
+            rows.iter().map(|row| {
+                vec![
+                    row.rank.to_string(),
+                    row.division_rank.to_string(),
+                    row.name.to_string(),
+                    times.format(row.time),
+                    row.division.to_string(),
+                    row.sex.map(|sex| sex.to_string()).unwrap_or_default(),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Edition {
+    division: String,
+    year: String,
+    finishers: usize,
+    winner: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+}
+
+// directory has one subdirectory of division files per year.
+pub(crate) fn history(
+    directory: &Path,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let mut years = visible_entries(directory, true)?;
+    years.sort();
+    let mut editions = vec![];
+    for year in years {
+        let race = load(&year, loader)?;
+        for division in race.events() {
+            let finishers = race
+                .finishers
+                .iter()
+                .filter(|finisher| finisher.event.as_deref() == Some(division));
+            let count = finishers.clone().count();
+            // events only has divisions that have finishers.
+            let winner = finishers.min_by_key(|finisher| finisher.time).unwrap();
+            editions.push(Edition {
+                division: division.to_string(),
+                year: file_name(&year),
+                finishers: count,
+                winner: winner.name.clone(),
+                time: winner.time,
+            });
+        }
+    }
+    // Stable, so each division's years stay in order.
+    editions.sort_by(|e1, e2| e1.division.cmp(&e2.division));
+
+    match output {
+        Output::Text => {
+            let name_width = editions.iter().map(|e| e.winner.len()).max().unwrap_or(0);
+            let mut division = None;
+            for edition in &editions {
+                if division != Some(&edition.division) {
+                    division = Some(&edition.division);
+                    println!("{}", edition.division);
+                }
+                println!(
+                    "  {} {:>5} {:name_width$} {:>10.1}",
+                    edition.year, edition.finishers, edition.winner, edition.time
+                );
+            }
+        }
+        Output::Json => write_json(&editions)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            ["division", "year", "finishers", "winner", "time"]
+                .map(String::from)
+                .to_vec(),
+            editions.iter().map(|edition| {
+                vec![
+                    edition.division.clone(),
+                    edition.year.clone(),
+                    edition.finishers.to_string(),
+                    edition.winner.clone(),
+                    times.format(edition.time),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_division() {
+        use MaleOrFemale::*;
+
+        assert_eq!(
+            division(Path::new("bmdm-2026-solo-female-military-heavy.json")),
+            Some(("solo-female-military-heavy".to_string(), Some(Female)))
+        );
+        assert_eq!(
+            division(Path::new("assets/bmdm/2016/bmdm-2016-solo-male-heavy.json")),
+            Some(("solo-male-heavy".to_string(), Some(Male)))
+        );
+        assert_eq!(
+            division(Path::new("bmdm-2026-team-light.json")),
+            Some(("team-light".to_string(), None))
+        );
+        assert_eq!(division(Path::new("2022.mhtml")), None);
+    }
+}
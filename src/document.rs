@@ -0,0 +1,76 @@
+// Every parser is tried on every file until one recognizes it, so
+// rather than have each parser re-parse the same text, a Document
+// parses it into whatever form is asked for (JSON or a DOM) the first
+// time it's needed and hands the same one to every parser after that.
+// Large MHTML files (e.g., WSER or Cocodona) used to be parsed into a
+// DOM four times.
+
+use {
+    scraper::Html,
+    serde_json::Value,
+    std::{cell::OnceCell, str::FromStr},
+};
+
+pub(crate) struct Document<'a> {
+    text: &'a str,
+    json: OnceCell<Option<Value>>,
+    json_lines: OnceCell<Option<Vec<Value>>>,
+    html: OnceCell<Html>,
+}
+
+impl<'a> Document<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            json: OnceCell::new(),
+            json_lines: OnceCell::new(),
+            html: OnceCell::new(),
+        }
+    }
+
+    pub(crate) fn text(&self) -> &'a str {
+        self.text
+    }
+
+    // The whole text as a single JSON value.
+    pub(crate) fn json(&self) -> Option<&Value> {
+        self.json
+            .get_or_init(|| Value::from_str(self.text).ok())
+            .as_ref()
+    }
+
+    // Some results are one JSON value per line, rather than a single
+    // value.
+    pub(crate) fn json_lines(&self) -> Option<&[Value]> {
+        self.json_lines
+            .get_or_init(|| {
+                self.text
+                    .trim()
+                    .split('\n')
+                    .map(Value::from_str)
+                    .collect::<Result<_, _>>()
+                    .ok()
+            })
+            .as_deref()
+    }
+
+    pub(crate) fn html(&self) -> &Html {
+        self.html.get_or_init(|| Html::parse_document(self.text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines() {
+        let document = Document::new("[1, 2]\n[3]\n");
+        assert_eq!(document.json_lines().map(<[_]>::len), Some(2));
+        assert!(document.json().is_none());
+
+        let document = Document::new("<html><body>[1]</body></html>");
+        assert!(document.json_lines().is_none());
+        assert_eq!(document.html().root_element().value().name(), "html");
+    }
+}
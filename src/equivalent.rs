@@ -0,0 +1,370 @@
+// A series' categories mix distances: the long trail category has 50ks
+// and a 53k, and the short trail category has a 6 miler alongside 5ks
+// and 10ks, so a runner's races can't be compared by time, or by
+// percent of the winner's time.  Instead, each result is turned into
+// equivalent times at standard distances, using either Riegel's formula,
+//
+//     t2 = t1 * (d2 / d1) ^ exponent
+//
+// whose exponent (1.06 by default) is how much a runner slows as the
+// distance grows, or Jack Daniels' VDOT, the VO2 max that the result
+// implies, which gives a time at any distance.  Each runner's best
+// result, i.e., the one with the fastest equivalent times, is the one
+// their predictions come from.
+//
+// A race's distance is what its details say (see details.rs) or, for
+// races without details, what its name or its category's name give
+// away, e.g., Forever Young 6 Miler or half-marathon.
+//
+// A result whose predictions are too slow to be a Duration (e.g., a
+// short race predicted at a much longer distance) is skipped with a
+// warning.
+
+use {
+    crate::{
+        Duration, Finisher, Loader, MaleOrFemale,
+        details::{meters, meters_in_name},
+        names,
+        output::{Output, Times, ranks, seconds, write_json, write_table},
+        pace,
+    },
+    anyhow::{Result, anyhow, bail},
+    clap::ValueEnum,
+    serde::Serialize,
+    std::{borrow::Cow, collections::HashMap, path::Path, str::FromStr},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub(crate) enum Model {
+    /// Riegel's formula, with --exponent
+    #[default]
+    Riegel,
+    /// Jack Daniels' VDOT
+    Vdot,
+}
+
+pub(crate) const DEFAULT_EXPONENT: f64 = 1.06;
+
+/// Riegel's exponent, which has to be more than 0 and at most 2, since
+/// anything else predicts nonsense, if it predicts anything at all.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Exponent(pub(crate) f64);
+
+impl FromStr for Exponent {
+    type Err = anyhow::Error;
+
+    fn from_str(exponent: &str) -> Result<Self> {
+        let exponent = exponent
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| anyhow!("{exponent:?} isn't a number"))?;
+        if !(exponent > 0.0 && exponent <= 2.0) {
+            bail!("the exponent must be more than 0 and at most 2, e.g., {DEFAULT_EXPONENT}");
+        }
+        Ok(Self(exponent))
+    }
+}
+
+/// A standard distance to predict times at, e.g., 5K or half marathon.
+#[derive(Clone, Debug)]
+pub(crate) struct Distance {
+    label: String,
+    meters: f64,
+}
+
+impl FromStr for Distance {
+    type Err = anyhow::Error;
+
+    fn from_str(label: &str) -> Result<Self> {
+        let meters = meters(label, None)
+            .ok_or_else(|| anyhow!("{label:?} isn't a distance, e.g., 5K or 13.1 mi"))?;
+        Ok(Self {
+            label: label.to_string(),
+            meters,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Predictor {
+    pub(crate) model: Model,
+    pub(crate) exponent: f64,
+}
+
+impl Predictor {
+    // The time that running meters in time is equivalent to at
+    // to_meters, if it's small enough to be a Duration.
+    fn predict(self, meters: f64, time: Duration, to_meters: f64) -> Option<Duration> {
+        let minutes = time.as_secs_f64() / 60.0;
+        let predicted = match self.model {
+            Model::Riegel => minutes * (to_meters / meters).powf(self.exponent),
+            Model::Vdot => vdot_minutes(to_meters, vdot(meters, minutes)),
+        };
+        pace::from_secs(predicted * 60.0)
+    }
+}
+
+// Daniels and Gilbert's formulas: the oxygen cost of running at a
+// speed, divided by the fraction of VO2 max that can be sustained for
+// that long.
+fn vdot(meters: f64, minutes: f64) -> f64 {
+    let velocity = meters / minutes;
+    let vo2 = -4.60 + 0.182_258 * velocity + 0.000_104 * velocity * velocity;
+    let fraction = 0.8
+        + 0.189_439_3 * (-0.012_778 * minutes).exp()
+        + 0.298_955_8 * (-0.193_260_5 * minutes).exp();
+    vo2 / fraction
+}
+
+// The VDOT formula can't be solved for time, but VDOT goes down as the
+// time goes up, so the time is found by bisection, somewhere between a
+// world record pace and a walk.
+fn vdot_minutes(meters: f64, target: f64) -> f64 {
+    let (mut fast, mut slow) = (meters / 500.0, meters / 30.0);
+    for _ in 0..100 {
+        let middle = (fast + slow) / 2.0;
+        if vdot(meters, middle) > target {
+            fast = middle;
+        } else {
+            slow = middle;
+        }
+    }
+    (fast + slow) / 2.0
+}
+
+// A runner's result with the fastest equivalent time, so far.
+struct Best<'a> {
+    count: usize,
+    equivalent: Duration,
+    race: String,
+    finisher: &'a Finisher,
+    /// at each of the distances
+    predictions: Vec<Duration>,
+}
+
+#[derive(Serialize)]
+struct Row {
+    rank: usize,
+    name: String,
+    sex: Option<MaleOrFemale>,
+    /// how many of the runner's results have a known distance
+    count: usize,
+    /// the race the predictions come from
+    race: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+    predictions: Vec<Prediction>,
+}
+
+#[derive(Serialize)]
+struct Prediction {
+    distance: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+}
+
+pub(crate) fn summarize(
+    directory: &Path,
+    predictor: Predictor,
+    distances: &[Distance],
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let categories = loader.series(directory)?;
+    categories
+        .iter()
+        .flat_map(|category| &category.races)
+        .try_for_each(|race| loader.check(race))?;
+    // Every result is compared at the first distance, since whichever
+    // result is best there is best at every distance.
+    let to_meters = distances.first().map_or(5000.0, |distance| distance.meters);
+    let mut best = HashMap::<String, Best>::new();
+    for category in &categories {
+        for race in &category.races {
+            let race_name = race.name();
+            let Some(meters) = race
+                .details
+                .meters()
+                .or_else(|| meters_in_name(&race_name))
+                .or_else(|| meters_in_name(&category.name))
+            else {
+                eprintln!("No distance for {}, skipping", race.path.display());
+                continue;
+            };
+            for finisher in &race.finishers {
+                let predict = |to_meters| predictor.predict(meters, finisher.time, to_meters);
+                let (Some(equivalent), Some(predictions)) = (
+                    predict(to_meters),
+                    distances
+                        .iter()
+                        .map(|distance| predict(distance.meters))
+                        .collect::<Option<Vec<_>>>(),
+                ) else {
+                    eprintln!(
+                        "Can't predict times from {}'s {:.1} in {}, skipping",
+                        finisher.name,
+                        finisher.time,
+                        race.path.display()
+                    );
+                    continue;
+                };
+                let name = names::canonical(Cow::from(&finisher.name)).into_owned();
+                let candidate = Best {
+                    count: 1,
+                    equivalent,
+                    race: race_name.clone(),
+                    finisher,
+                    predictions,
+                };
+                match best.get_mut(&name) {
+                    None => {
+                        best.insert(name, candidate);
+                    }
+                    Some(best) => {
+                        let count = best.count + 1;
+                        if candidate.equivalent < best.equivalent {
+                            *best = candidate;
+                        }
+                        best.count = count;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut rows = best
+        .into_iter()
+        .map(|(name, best)| Row {
+            rank: 0,
+            name,
+            sex: best.finisher.morf,
+            count: best.count,
+            race: best.race,
+            time: best.finisher.time,
+            predictions: distances
+                .iter()
+                .zip(best.predictions)
+                .map(|(distance, time)| Prediction {
+                    distance: distance.label.clone(),
+                    time,
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|r1, r2| (first_prediction(r1), &r1.name).cmp(&(first_prediction(r2), &r2.name)));
+    let ranks = ranks(&rows, first_prediction);
+    for (row, rank) in rows.iter_mut().zip(ranks) {
+        row.rank = rank;
+    }
+
+    match output {
+        Output::Text => {
+            let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+            print!("{:>4} {:name_width$} {:1}", "", "", "");
+            for distance in distances {
+                print!(" {:>13}", distance.label);
+            }
+            println!();
+            for row in &rows {
+                print!(
+                    "{:>4} {:name_width$} {:1}",
+                    row.rank,
+                    row.name,
+                    row.sex.map(|sex| sex.to_string()).unwrap_or_default()
+                );
+                for prediction in &row.predictions {
+                    print!(" {:>13.1}", prediction.time);
+                }
+                println!("  {} ({:.1})", row.race, row.time);
+            }
+        }
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => {
+            let mut header = ["rank", "name", "sex", "count", "race", "time"]
+                .map(String::from)
+                .to_vec();
+            header.extend(distances.iter().map(|distance| distance.label.clone()));
+            write_table(
+                output,
+                header,
+                rows.iter().map(|row| {
+                    let mut record = vec![
+                        row.rank.to_string(),
+                        row.name.clone(),
+                        row.sex.map(|sex| sex.to_string()).unwrap_or_default(),
+                        row.count.to_string(),
+                        row.race.clone(),
+                        times.format(row.time),
+                    ];
+                    record.extend(
+                        row.predictions
+                            .iter()
+                            .map(|prediction| times.format(prediction.time)),
+                    );
+                    record
+                }),
+            )?
+        }
+    }
+    Ok(())
+}
+
+fn first_prediction(row: &Row) -> Option<Duration> {
+    row.predictions.first().map(|prediction| prediction.time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(duration: Duration) -> f64 {
+        duration.as_secs_f64() / 60.0
+    }
+
+    #[test]
+    fn test_riegel() {
+        let predictor = Predictor {
+            model: Model::Riegel,
+            exponent: DEFAULT_EXPONENT,
+        };
+        let time = Duration::new(20 * 60, 0);
+        let ten_k = minutes(predictor.predict(5000.0, time, 10_000.0).unwrap());
+        assert!((ten_k - 20.0 * 2f64.powf(1.06)).abs() < 1e-6);
+        assert_eq!(predictor.predict(5000.0, time, 5000.0), Some(time));
+        let slower = Predictor {
+            exponent: 1.15,
+            ..predictor
+        };
+        assert!(slower.predict(5000.0, time, 10_000.0) > predictor.predict(5000.0, time, 10_000.0));
+        assert_eq!(predictor.predict(1.0, time, f64::MAX), None);
+
+        assert_eq!("1.15".parse::<Exponent>().unwrap().0, 1.15);
+        for bad in ["NaN", "inf", "1000", "0", "-1", "fast"] {
+            assert!(bad.parse::<Exponent>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_vdot() {
+        // From Daniels' tables: a 19:57 5K is a VDOT of 50, which is
+        // equivalent to a 41:21 10K and a 3:10:49 marathon.
+        assert!((vdot(5000.0, 19.95) - 50.0).abs() < 0.1);
+        let predictor = Predictor {
+            model: Model::Vdot,
+            exponent: DEFAULT_EXPONENT,
+        };
+        let time = Duration::new(19 * 60 + 57, 0);
+        let ten_k = minutes(predictor.predict(5000.0, time, 10_000.0).unwrap());
+        assert!((ten_k - (41.0 + 21.0 / 60.0)).abs() < 0.25, "{ten_k}");
+        let marathon = minutes(predictor.predict(5000.0, time, 42_195.0).unwrap());
+        assert!((marathon - (190.0 + 49.0 / 60.0)).abs() < 1.0, "{marathon}");
+    }
+
+    #[test]
+    fn test_distance() {
+        let distance = "half marathon".parse::<Distance>().unwrap();
+        assert_eq!(distance.meters, 21_097.5);
+        assert!("2025".parse::<Distance>().is_err());
+    }
+}
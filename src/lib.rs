@@ -1,46 +1,235 @@
 use parser::race_result_mhtml;
 
+pub use {
+    age_groups::AgeGroups,
+    cache::Cache,
+    details::{RaceDetails, Surface, Units},
+    digital_duration_nom::duration::Duration,
+    scoring::{Method, Points, Pools, Rounding, Scoring},
+    warning::Warning,
+};
+
+use {document::Document, warning::OnWarning};
+
+mod age_groups;
+mod athlete;
+mod awards;
+mod cache;
+mod convert;
+mod details;
+mod divisions;
+mod document;
+mod equivalent;
 mod hashes;
+mod manifest;
 mod names;
+mod output;
+mod pace;
 mod parser;
+mod query;
+mod records;
+mod report;
+mod scoring;
+mod show;
+mod stats;
+mod streaks;
+mod warning;
 
 use {
     crate::parser::{
-        ancient_ultra_signup, athlinks, ccr_timing, chrono_track, csv, its_your_race, race_roster,
-        run_fit, runsignup, runsignup_20240506_mhtml, runsignup_mhtml, taos, ultra_signup,
-        ultra_signup_mhtml, web_scorer,
+        ancient_ultra_signup, athlinks, canonical, ccr_timing, chrono_track, csv, its_your_race,
+        race_roster, run_fit, runsignup, runsignup_20240506_mhtml, runsignup_mhtml, taos,
+        ultra_signup, ultra_signup_mhtml, web_scorer,
     },
     anyhow::{Error, Result, bail},
-    clap::Parser,
-    digital_duration_nom::duration::Duration,
-    itertools::Itertools,
+    clap::{Parser, Subcommand},
     mail_parser::MessageParser,
+    output::{Output, Times, all_seconds, ranks, seconds, write_json, write_table},
+    rayon::{
+        ThreadPoolBuilder,
+        iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator},
+    },
     reqwest::Url,
-    serde::Deserialize,
+    serde::{Deserialize, Serialize},
     std::{
         borrow::Cow,
         cmp::Reverse,
         collections::HashMap,
-        fmt::{self, Display, Formatter},
-        fs::{self, DirEntry, File},
-        io::{self, Read},
+        fmt::{self, Debug, Display, Formatter},
+        fs::{self, DirEntry},
+        io,
         path::{Path, PathBuf},
         str::FromStr,
     },
 };
 
 pub fn summarize(config: &Config) -> Result<()> {
-    if config.results.len() == 1
-        && let Source::File(p) = &config.results[0]
-        && p.is_dir()
-    {
-        return summarize_scores(p);
+    ThreadPoolBuilder::new()
+        .num_threads(config.jobs.unwrap_or(0))
+        .build()?
+        .install(|| summarize_command(config))
+}
+
+fn summarize_command(config: &Config) -> Result<()> {
+    match &config.command {
+        Some(Command::Streaks {
+            not_held,
+            directory,
+        }) => streaks::summarize(directory, not_held, config.output, &config.loader()),
+        Some(Command::Query { expression, groups }) => query::summarize(
+            expression,
+            groups,
+            config.output,
+            config.times,
+            &config.loader(),
+        ),
+        Some(Command::Cache {
+            command: CacheCommand::Prune { all },
+        }) => {
+            let cache = Cache::open()?;
+            let removed = cache.prune(*all)?;
+            println!(
+                "Removed {removed} file(s) from {}",
+                cache.directory().display()
+            );
+            Ok(())
+        }
+        Some(Command::Report { html, series }) => {
+            report::generate(series, html, &config.scoring(), &config.loader())
+        }
+        Some(Command::Sum { results }) => summarize_total_times(results, config),
+        Some(Command::Category { directory }) => {
+            let loader = config.loader();
+            let category = loader.category(directory)?;
+            category
+                .races
+                .iter()
+                .try_for_each(|race| loader.check(race))?;
+            summarize_files(category.races, &config.scoring(), config.output)
+        }
+        Some(Command::Series { directory }) => summarize_series(
+            directory,
+            &config.scoring(),
+            config.output,
+            &config.loader(),
+        ),
+        Some(Command::Show { file }) => {
+            show::summarize(file, config.output, config.times, &config.loader())
+        }
+        Some(Command::Divisions { history, directory }) => {
+            let loader = config.loader();
+            if *history {
+                divisions::history(directory, config.output, config.times, &loader)
+            } else {
+                divisions::rankings(directory, config.output, config.times, &loader)
+            }
+        }
+        Some(Command::Convert {
+            input,
+            out,
+            name,
+            date,
+            distance,
+        }) => {
+            let details = RaceDetails {
+                name: name.clone(),
+                date: date.clone(),
+                distance: distance.clone(),
+                ..RaceDetails::default()
+            };
+            convert::convert(input, out, details, &config.loader())
+        }
+        Some(Command::Pace {
+            units,
+            sort,
+            results,
+        }) => pace::summarize(
+            results,
+            *units,
+            *sort,
+            config.output,
+            config.times,
+            &config.loader(),
+        ),
+        Some(Command::Equivalent {
+            model,
+            exponent,
+            distances,
+            directory,
+        }) => {
+            let predictor = equivalent::Predictor {
+                model: *model,
+                exponent: exponent.0,
+            };
+            equivalent::summarize(
+                directory,
+                predictor,
+                distances,
+                config.output,
+                config.times,
+                &config.loader(),
+            )
+        }
+        Some(Command::Athlete { name, paths }) => {
+            athlete::summarize(name, paths, config.output, config.times, &config.loader())
+        }
+        Some(Command::Records { top, directory }) => records::summarize(
+            directory,
+            *top,
+            &config.age_groups,
+            config.output,
+            config.times,
+            &config.loader(),
+        ),
+        Some(Command::Awards {
+            overall,
+            top,
+            double,
+            race,
+        }) => {
+            let rules = awards::Rules {
+                age_groups: config.age_groups.clone(),
+                overall: *overall,
+                top: *top,
+                double: *double,
+            };
+            awards::summarize(race, &rules, config.output, config.times, &config.loader())
+        }
+        Some(Command::Stats { results }) => stats::summarize(
+            results,
+            &config.age_groups,
+            config.output,
+            config.times,
+            &config.loader(),
+        ),
+        Some(Command::Events { files }) => show::events(files, config.output, &config.loader()),
+        // Without a subcommand, the mode is inferred from the arguments:
+        // a single directory is a series if its first entry is a
+        // directory and a category otherwise, and anything else is
+        // summed.  A stray file (e.g., a NOTE or .DS_Store) can change
+        // which mode is picked, which is why the subcommands exist.
+        None => match &config.results[..] {
+            [Source::File(directory)] if directory.is_dir() => summarize_scores(
+                directory,
+                &config.scoring(),
+                config.output,
+                &config.loader(),
+            ),
+            results => summarize_total_times(results, config),
+        },
     }
-    summarize_total_times(config)
 }
 
-fn summarize_scores(p: &Path) -> Result<()> {
-    let mut entries = fs::read_dir(p)?.peekable();
+fn summarize_scores(
+    directory: &Path,
+    scoring: &Scoring,
+    output: Output,
+    loader: &Loader,
+) -> Result<()> {
+    if directory.join(manifest::FILE_NAME).exists() {
+        return summarize_series(directory, scoring, output, loader);
+    }
+    let mut entries = fs::read_dir(directory)?.peekable();
 
     match entries.peek() {
         None => Ok(()),
@@ -54,9 +243,22 @@ fn summarize_scores(p: &Path) -> Result<()> {
                 }
                 Ok(p) => {
                     if p.file_type()?.is_dir() {
-                        summarize_directories(entries)
+                        let categories = load_entries(entries, |p| {
+                            category_with(&p, &|path: PathBuf| loader.results(&path))
+                        })?;
+                        categories
+                            .iter()
+                            .flat_map(|category| &category.races)
+                            .try_for_each(|race| loader.check(race))?;
+                        summarize_directories(
+                            categories,
+                            &scoring.resolve(Some(directory))?,
+                            output,
+                        )
                     } else {
-                        summarize_files(entries)
+                        let races = load_entries(entries, |p| loader.results(&p))?;
+                        races.iter().try_for_each(|race| loader.check(race))?;
+                        summarize_files(races, scoring, output)
                     }
                 }
             }
@@ -64,187 +266,696 @@ fn summarize_scores(p: &Path) -> Result<()> {
     }
 }
 
-fn summarize_directories(entries: impl Iterator<Item = io::Result<DirEntry>>) -> Result<()> {
-    let (paths, scores) = score_directories(entries)?;
-    let mut scores = scores
+fn summarize_series(
+    directory: &Path,
+    scoring: &Scoring,
+    output: Output,
+    loader: &Loader,
+) -> Result<()> {
+    let categories = loader.series(directory)?;
+    categories
+        .iter()
+        .flat_map(|category| &category.races)
+        .try_for_each(|race| loader.check(race))?;
+    summarize_directories(categories, &scoring.resolve(Some(directory))?, output)
+}
+
+fn summarize_directories(
+    categories: Vec<Category>,
+    scoring: &Scoring,
+    output: Output,
+) -> Result<()> {
+    let rows = score_series_with(&categories, scoring);
+    match output {
+        Output::Text => print_series(&rows),
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_series_table(output, &categories, &rows)?,
+    }
+    Ok(())
+}
+
+/// The finishers of one race, as parsed from a results file.
+#[derive(Clone, Debug)]
+pub struct RaceResults {
+    /// the file the results came from
+    pub path: PathBuf,
+    pub finishers: Vec<Finisher>,
+    /// problems the parser ran into, e.g., rows it had to skip
+    pub warnings: Vec<Warning>,
+    pub details: RaceDetails,
+    /// how many started but didn't finish (including those who were
+    /// disqualified), for results that say
+    pub did_not_finish: Option<usize>,
+    /// who the results list as not finishing, including those who
+    /// didn't start, for results that list them
+    pub non_finishers: Vec<NonFinisher>,
+}
+
+impl RaceResults {
+    /// The race's name, which is its display name if it has one and
+    /// its file name without the extension otherwise.
+    pub fn name(&self) -> String {
+        match &self.details.name {
+            Some(name) => name.clone(),
+            None => self
+                .path
+                .file_stem()
+                .unwrap_or(self.path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// The events or divisions the results list finishers under, in the
+    /// order they first appear.  Empty for results that don't have any.
+    pub fn events(&self) -> Vec<&str> {
+        let mut events = vec![];
+        for event in self.finishers.iter().filter_map(|f| f.event.as_deref()) {
+            if !events.contains(&event) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Keeps only the finishers of one event, ignoring case.  It's an
+    /// error if there's no such event, since otherwise a typo would look
+    /// like a race that nobody finished.
+    pub fn select_event(mut self, event: &str) -> Result<Self> {
+        let event = event.trim();
+        let events = self.events();
+        if !events.iter().any(|e| e.eq_ignore_ascii_case(event)) {
+            if events.is_empty() {
+                bail!("{} doesn't list any events", self.path.display());
+            }
+            bail!(
+                "{} has no event {event:?}; its events are {}",
+                self.path.display(),
+                events.join(", ")
+            );
+        }
+        self.finishers.retain(|finisher| {
+            finisher
+                .event
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(event))
+        });
+        // Results that say how many didn't finish don't say who was in
+        // which event.
+        self.did_not_finish = None;
+        self.non_finishers.clear();
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finisher {
+    /// the name as it appears in the results, before aliases are applied
+    pub name: String,
+    pub time: Duration,
+    /// None when the results don't say
+    pub morf: Option<MaleOrFemale>,
+    /// the event or division (e.g., 10K or Female - Overall) the
+    /// finisher is listed under, for results that have them
+    pub event: Option<String>,
+    /// the pace the results list, for results that have one, which is
+    /// per mile or per kilometer (the results rarely say)
+    pub pace: Option<Duration>,
+    /// the age on race day, for results that list it
+    pub age: Option<u8>,
+}
+
+/// Someone the results list without a finish.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NonFinisher {
+    /// the name as it appears in the results, before aliases are applied
+    pub name: String,
+    pub status: Status,
+}
+
+/// Why a runner in the results didn't finish.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Status {
+    DidNotFinish,
+    DidNotStart,
+    Disqualified,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Status::DidNotFinish => "DNF",
+                Status::DidNotStart => "DNS",
+                Status::Disqualified => "DQ",
+            }
+        )
+    }
+}
+
+/// Reads and parses a results file (HTML, MHTML, JSON or CSV from any
+/// of the timing companies runs knows about).  A file that none of the
+/// parsers recognize, e.g., a placeholder for a race that wasn't held,
+/// has no finishers.  The race's details come from its sidecar, if it
+/// has one.
+pub fn load_results(path: impl AsRef<Path>) -> Result<RaceResults> {
+    let path = path.as_ref();
+    details::with_sidecar(parse(path, &fs::read(path)?))
+}
+
+fn parse(path: &Path, bytes: &[u8]) -> RaceResults {
+    let contents = contents(bytes);
+    let document = Document::new(&contents);
+    let mut warnings = vec![];
+    let finishers = PARSERS
+        .iter()
+        .find_map(|parser| {
+            // Only the warnings from the parser that recognizes the
+            // file are interesting.
+            warnings.clear();
+            parser(&document, &mut warnings)
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, time, morf, event, pace, age)| Finisher {
+            name: name.into_owned(),
+            time,
+            morf,
+            event: event.map(Cow::into_owned),
+            pace,
+            age,
+        })
+        .collect();
+    RaceResults {
+        path: path.to_path_buf(),
+        finishers,
+        warnings,
+        details: canonical::details(&document).unwrap_or_default(),
+        did_not_finish: DID_NOT_FINISH.iter().find_map(|count| count(&document)),
+        non_finishers: NON_FINISHERS
+            .iter()
+            .find_map(|non_finishers| non_finishers(&document))
+            .unwrap_or_default(),
+    }
+}
+
+/// Combines the results of a race that was posted as several files,
+/// e.g., separate pages for women and men, into a single race that goes
+/// by the first file's path.  Each file can come with the sex of
+/// everyone in it, which is given to the finishers whose sex the file
+/// itself doesn't say.  None if there are no files.
+pub fn combine_results(
+    parts: impl IntoIterator<Item = (RaceResults, Option<MaleOrFemale>)>,
+) -> Option<RaceResults> {
+    parts
+        .into_iter()
+        .map(|(mut race, sex)| {
+            for finisher in &mut race.finishers {
+                finisher.morf = finisher.morf.or(sex);
+            }
+            race
+        })
+        .reduce(|mut combined, mut race| {
+            combined.finishers.append(&mut race.finishers);
+            combined.warnings.append(&mut race.warnings);
+            combined.did_not_finish = combined
+                .did_not_finish
+                .zip(race.did_not_finish)
+                .map(|(combined, race)| combined + race);
+            combined.non_finishers.append(&mut race.non_finishers);
+            combined
+        })
+}
+
+/// The races in one category of a series, e.g., all the 5ks.
+#[derive(Clone, Debug)]
+pub struct Category {
+    /// the name of the category's directory, or the category given in
+    /// series.toml
+    pub name: String,
+    pub races: Vec<RaceResults>,
+}
+
+/// Loads every results file in a category directory, skipping hidden
+/// files, sidecars and subdirectories.
+pub fn load_category(directory: impl AsRef<Path>) -> Result<Category> {
+    visible_category(directory.as_ref(), &|path: &Path| load_results(path))
+}
+
+/// Loads a series directory, which has one subdirectory per category,
+/// or, if it has a series.toml, the races that the series.toml lists.
+/// Without a series.toml, hidden directories and plain files (e.g., a
+/// NOTE about the series) are skipped.
+pub fn load_series(directory: impl AsRef<Path>) -> Result<Vec<Category>> {
+    visible_series(directory.as_ref(), &|path: &Path| load_results(path))
+}
+
+// The library and the command line load categories and series the same
+// way, but the command line loads each file with its Loader.
+fn visible_category(
+    directory: &Path,
+    load: &(impl Fn(&Path) -> Result<RaceResults> + Sync),
+) -> Result<Category> {
+    Ok(Category {
+        name: file_name(directory),
+        races: load_in_order(visible_entries(directory, false)?, |path| load(&path))?,
+    })
+}
+
+fn visible_series(
+    directory: &Path,
+    load: &(impl Fn(&Path) -> Result<RaceResults> + Sync),
+) -> Result<Vec<Category>> {
+    match manifest::load(directory, load)? {
+        Some(categories) => Ok(categories),
+        None => load_in_order(visible_entries(directory, true)?, |path| {
+            visible_category(&path, load)
+        }),
+    }
+}
+
+fn category_with(
+    directory: &Path,
+    load: &(impl Fn(PathBuf) -> Result<RaceResults> + Sync),
+) -> Result<Category> {
+    Ok(Category {
+        name: file_name(directory),
+        races: load_entries(fs::read_dir(directory)?, load)?,
+    })
+}
+
+fn load_entries<T: Send>(
+    entries: impl Iterator<Item = io::Result<DirEntry>>,
+    load: impl Fn(PathBuf) -> Result<T> + Sync + Send,
+) -> Result<Vec<T>> {
+    let mut paths = entries
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| !details::is_sidecar(path));
+    load_in_order(paths, load)
+}
+
+// The entries of a directory that are directories (or aren't), leaving
+// out hidden ones and sidecars, in read_dir order.
+fn visible_entries(directory: &Path, directories: bool) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if !entry.file_name().to_string_lossy().starts_with('.')
+            && entry.path().is_dir() == directories
+            && !details::is_sidecar(&entry.path())
+        {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
+
+// Loads files in parallel, but returns them (or the first error) in the
+// order they were given, so that the output and warnings don't depend
+// on which file happened to finish parsing first.
+fn load_in_order<I, T>(
+    items: I,
+    load: impl Fn(I::Item) -> Result<T> + Sync + Send,
+) -> Result<Vec<T>>
+where
+    I: IntoParallelIterator,
+    I::Iter: IndexedParallelIterator,
+    T: Send,
+{
+    items
+        .into_par_iter()
+        .map(load)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// One runner's standing in a series: their best race in each category
+/// counts toward their total.
+#[derive(Debug, Serialize)]
+pub struct SeriesRow {
+    pub rank: usize,
+    pub name: String,
+    pub points: u32,
+    pub count: usize,
+    pub races: Vec<RacePoints>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RacePoints {
+    pub category: String,
+    pub race: String,
+    pub points: u16,
+    /// how many races the runner finished in this category
+    pub count: u8,
+    /// the index of the category in the categories that were scored
+    #[serde(skip)]
+    pub category_index: usize,
+}
+
+/// Scores a series, ranking everyone by the sum of their best points in
+/// each category.
+pub fn score_series(categories: &[Category]) -> Vec<SeriesRow> {
+    score_series_with(categories, &Scoring::default())
+}
+
+/// Scores a series like score_series, with points from scoring.
+pub fn score_series_with(categories: &[Category], scoring: &Scoring) -> Vec<SeriesRow> {
+    let races = series_races(categories);
+    let mut scores = score_directories(categories, scoring)
         .into_iter()
         .map(|(name, scores)| {
             (
                 name,
                 scores
                     .iter()
-                    .map(|ScoreInfo { points, count, .. }| (*points, *count))
+                    .map(|ScoreInfo { points, count, .. }| {
+                        (u32::from(*points), usize::from(*count))
+                    })
                     .reduce(|(total_points, total_count), (points, count)| {
                         (total_points + points, total_count + count)
-                    }),
+                    })
+                    .unwrap(),
                 scores,
             )
         })
         .collect::<Vec<_>>();
     scores.sort_by_key(|(_, points, _)| Reverse(*points));
-    let rank_width = scores.len().ilog10() as usize + 1;
-    let mut old_rank = 1;
-    let mut old_points = 0;
-    let mut upcoming_rank = 1;
+    let ranks = ranks(&scores, |(_, (points, _), _)| *points);
+    scores
+        .into_iter()
+        .zip(ranks)
+        .map(|((name, (points, count), events), rank)| SeriesRow {
+            rank,
+            name,
+            points,
+            count,
+            races: events
+                .into_iter()
+                .map(
+                    |ScoreInfo {
+                         points,
+                         path_index,
+                         count,
+                         ..
+                     }| {
+                        let (category_index, race) = races[path_index as usize];
+                        RacePoints {
+                            category: categories[category_index].name.clone(),
+                            race: race.name(),
+                            points,
+                            count,
+                            category_index,
+                        }
+                    },
+                )
+                .collect(),
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+fn print_series(rows: &[SeriesRow]) {
+    let rank_width = rows.len().max(1).ilog10() as usize + 1;
     let mut need_nl = false;
-    for (name, points_and_counts, events) in scores {
-        let (points, count) = points_and_counts.unwrap();
+    for SeriesRow {
+        rank,
+        name,
+        points,
+        count,
+        races,
+    } in rows
+    {
         if need_nl {
             println!();
         } else {
             need_nl = true;
         }
-        let rank = if points == old_points {
-            old_rank
-        } else {
-            old_points = points;
-            old_rank = upcoming_rank;
-            upcoming_rank
-        };
-        upcoming_rank += 1;
         println!("{rank:>rank_width$} {points:>4} {count:>3} {name}");
-        for ScoreInfo {
-            points,
-            path_index,
-            count: _,
-        } in events
-        {
-            println!(
-                "{:rank_width$}  {points:>3} {count:>3}   {}",
-                "",
-                paths_indexed(&paths, path_index)
-            );
+        for RacePoints { race, points, .. } in races {
+            println!("{:rank_width$}  {points:>3} {count:>3}   {race}", "");
         }
     }
-    Ok(())
 }
 
-fn paths_indexed(paths: &[PathBuf], index: u8) -> Cow<'_, str> {
-    paths[index as usize].file_stem().unwrap().to_string_lossy()
+fn write_series_table(output: Output, categories: &[Category], rows: &[SeriesRow]) -> Result<()> {
+    let mut header = vec![
+        "rank".to_string(),
+        "name".to_string(),
+        "points".to_string(),
+        "count".to_string(),
+    ];
+    header.extend(categories.iter().map(|category| category.name.clone()));
+    write_table(
+        output,
+        header,
+        rows.iter().map(|row| {
+            let mut cells = vec![String::new(); categories.len()];
+            for race in &row.races {
+                cells[race.category_index] = race.points.to_string();
+            }
+            let mut record = vec![
+                row.rank.to_string(),
+                row.name.clone(),
+                row.points.to_string(),
+                row.count.to_string(),
+            ];
+            record.append(&mut cells);
+            record
+        }),
+    )
 }
 
-#[allow(clippy::type_complexity)]
+// Every race in a series, along with the index of its category, in the
+// order that score_directories numbers them.
+fn series_races(categories: &[Category]) -> Vec<(usize, &RaceResults)> {
+    categories
+        .iter()
+        .enumerate()
+        .flat_map(|(i, category)| category.races.iter().map(move |race| (i, race)))
+        .collect()
+}
+
+// The path_indexes are indexes into series_races.
 fn score_directories(
-    entries: impl Iterator<Item = io::Result<DirEntry>>,
-) -> Result<(Vec<PathBuf>, HashMap<String, Vec<ScoreInfo>>)> {
-    let mut paths = vec![];
-    fold_paths(
-        entries,
-        |p| p.read_dir().map_err(|e| e.into()).and_then(score_files),
-        |mut h: HashMap<_, Vec<ScoreInfo>>, (_i, (mut new_paths, scores))| {
-            let offset = paths.len() as u8;
-            paths.append(&mut new_paths);
-            for (name, mut score_info) in scores.into_iter() {
-                let ScoreInfo {
-                    ref mut path_index, ..
-                } = score_info;
-                *path_index += offset;
-                h.entry(name).or_default().push(score_info);
+    categories: &[Category],
+    scoring: &Scoring,
+) -> HashMap<String, Vec<ScoreInfo>> {
+    let mut h: HashMap<_, Vec<ScoreInfo>> = HashMap::new();
+    let mut offset = 0;
+    for category in categories {
+        for (name, mut score_info) in score_files(&category.races, scoring) {
+            let ScoreInfo {
+                ref mut path_index,
+                ref mut races,
+                ..
+            } = score_info;
+            *path_index += offset;
+            for race in races.iter_mut() {
+                race.path_index += offset;
             }
-            h
-        },
-    )
-    .map(|(_, h)| (paths, h))
+            h.entry(name).or_default().push(score_info);
+        }
+        offset += category.races.len() as u8;
+    }
+    h
 }
 
+// points and path_index are for the race with the most points, but
+// races has every race, since that's what's needed for one column per
+// race and for the HTML report.
 #[derive(Debug)]
 struct ScoreInfo {
     points: u16,
     path_index: u8,
     count: u8,
+    races: Vec<RaceInfo>,
 }
 
-fn fold_paths<IN, OUT>(
-    entries: impl Iterator<Item = io::Result<DirEntry>>,
-    p_to_in: impl Fn(&Path) -> Result<IN>,
-    f: impl FnMut(HashMap<String, OUT>, (usize, IN)) -> HashMap<String, OUT>,
-) -> Result<(Vec<PathBuf>, HashMap<String, OUT>)> {
-    let mut paths = vec![];
-    let scores = entries
-        .map(|entry| {
-            entry.map_err(|e| e.into()).and_then(|entry| {
-                paths.push(entry.path());
-                p_to_in(paths.last().unwrap())
-            })
-        })
-        .enumerate()
-        .map(|(i, r)| r.map(|v| (i, v)))
-        .fold_ok(HashMap::<String, OUT>::new(), f)?;
-    Ok((paths, scores))
+#[derive(Debug)]
+struct RaceInfo {
+    path_index: u8,
+    points: u16,
+    time: Duration,
+    morf: MaleOrFemale,
 }
 
-fn score_files(
-    entries: impl Iterator<Item = io::Result<DirEntry>>,
-) -> Result<(Vec<PathBuf>, HashMap<String, ScoreInfo>)> {
-    fold_paths(entries, contents, |mut h, (i, contents)| {
-        if let Some(mut names_and_times) = PARSERS.iter().find_map(|parser| parser(&contents)) {
-            names_and_times.sort_by_key(|&(_, time, _)| time);
-            let mut firsts = [None; 2];
-            for (name, time, morf) in names_and_times {
-                let time = (*time).as_secs();
-                if let Some(morf) = morf {
-                    let morf = morf as usize;
-                    if morf < 2 {
-                        let new_points = match &firsts[morf] {
-                            None => {
-                                firsts[morf] = Some(time);
-                                100
-                            }
-                            Some(first) => (first * 100 / time) as u16,
-                        };
-                        if let Some(ScoreInfo {
-                            points,
-                            path_index,
-                            count,
-                        }) = h.get_mut(name.as_ref())
-                        {
-                            *count += 1;
-                            if new_points > *points {
-                                *points = new_points;
-                                *path_index = i as u8;
-                            }
-                        } else {
-                            h.insert(
-                                names::canonical(name).into_owned(),
-                                ScoreInfo {
-                                    points: new_points,
-                                    path_index: i as u8,
-                                    count: 1,
-                                },
-                            );
-                        }
-                    }
+fn score_files(race_results: &[RaceResults], scoring: &Scoring) -> HashMap<String, ScoreInfo> {
+    let mut h = HashMap::new();
+    for (i, race_results) in race_results.iter().enumerate() {
+        for (Finisher { name, time, .. }, morf, new_points) in scoring.points(race_results) {
+            let race = RaceInfo {
+                path_index: i as u8,
+                points: new_points,
+                time: *time,
+                morf,
+            };
+            // Looked up by the canonical name, since that's what's
+            // inserted; otherwise someone whose name has an alias only
+            // gets credit for their last race.
+            let name = names::canonical(Cow::from(name)).into_owned();
+            if let Some(ScoreInfo {
+                points,
+                path_index,
+                count,
+                races,
+            }) = h.get_mut(&name)
+            {
+                *count += 1;
+                races.push(race);
+                if new_points > *points {
+                    *points = new_points;
+                    *path_index = i as u8;
                 }
+            } else {
+                h.insert(
+                    name,
+                    ScoreInfo {
+                        points: new_points,
+                        path_index: i as u8,
+                        count: 1,
+                        races: vec![race],
+                    },
+                );
             }
         }
-        h
-    })
+    }
+    h
+}
+
+fn summarize_files(category: Vec<RaceResults>, scoring: &Scoring, output: Output) -> Result<()> {
+    let rows = score_category_with(&category, &scoring.resolve(None)?);
+    match output {
+        Output::Text => print_category(&rows),
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_category_table(output, &category, &rows)?,
+    }
+    Ok(())
+}
+
+/// One runner's standing in a category: only their best race counts.
+#[derive(Debug, Serialize)]
+pub struct CategoryRow {
+    pub rank: usize,
+    pub name: String,
+    pub points: u16,
+    pub count: u8,
+    /// the race the points came from
+    pub race: String,
+    pub races: Vec<RaceScore>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RaceScore {
+    pub race: String,
+    pub points: u16,
+    /// the index of the race in the races that were scored
+    #[serde(skip)]
+    pub race_index: usize,
 }
 
-fn summarize_files(entries: impl Iterator<Item = io::Result<DirEntry>>) -> Result<()> {
-    let (paths, scores) = score_files(entries)?;
-    let mut scores = scores.into_iter().collect::<Vec<_>>();
+/// Scores the races of a category.  The first male and the first
+/// female in each race get 100 points and everyone else gets points
+/// in proportion to how close they were to the winner's time.
+pub fn score_category(races: &[RaceResults]) -> Vec<CategoryRow> {
+    score_category_with(races, &Scoring::default())
+}
+
+/// Scores the races of a category like score_category, with points
+/// from scoring.
+pub fn score_category_with(races: &[RaceResults], scoring: &Scoring) -> Vec<CategoryRow> {
+    let mut scores = score_files(races, scoring).into_iter().collect::<Vec<_>>();
 
     scores.sort_by_key(|&(_, ScoreInfo { points, .. })| Reverse(points));
-    let width = scores.iter().map(|(name, ..)| name.len()).max().unwrap();
-    for (
+    let ranks = ranks(&scores, |(_, ScoreInfo { points, .. })| *points);
+    scores
+        .into_iter()
+        .zip(ranks)
+        .map(
+            |(
+                (
+                    name,
+                    ScoreInfo {
+                        points,
+                        path_index,
+                        count,
+                        races: race_infos,
+                    },
+                ),
+                rank,
+            )| CategoryRow {
+                rank,
+                name,
+                points,
+                count,
+                race: races[path_index as usize].name(),
+                races: race_infos
+                    .into_iter()
+                    .map(
+                        |RaceInfo {
+                             path_index, points, ..
+                         }| RaceScore {
+                            race: races[path_index as usize].name(),
+                            points,
+                            race_index: path_index as usize,
+                        },
+                    )
+                    .collect(),
+            },
+        )
+        .collect()
+}
+
+fn write_category_table(output: Output, races: &[RaceResults], rows: &[CategoryRow]) -> Result<()> {
+    let mut header = vec![
+        "rank".to_string(),
+        "name".to_string(),
+        "points".to_string(),
+        "count".to_string(),
+    ];
+    header.extend(races.iter().map(RaceResults::name));
+    write_table(
+        output,
+        header,
+        rows.iter().map(|row| {
+            let mut cells = vec![String::new(); races.len()];
+            for race in &row.races {
+                cells[race.race_index] = race.points.to_string();
+            }
+            let mut record = vec![
+                row.rank.to_string(),
+                row.name.clone(),
+                row.points.to_string(),
+                row.count.to_string(),
+            ];
+            record.append(&mut cells);
+            record
+        }),
+    )
+}
+
+fn print_category(rows: &[CategoryRow]) {
+    let width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+    for CategoryRow {
         name,
-        ScoreInfo {
-            points,
-            path_index,
-            count,
-        },
-    ) in scores
+        points,
+        count,
+        race,
+        ..
+    } in rows
     {
-        println!(
-            "{points:>3}: {count:>3} {name:width$} {}",
-            paths_indexed(&paths, path_index)
-        );
+        println!("{points:>3}: {count:>3} {name:width$} {race}");
     }
-    Ok(())
 }
 
-static PARSERS: [fn(&str) -> OptionalResults; 16] = [
+static PARSERS: [for<'a> fn(&Document<'a>, &mut Vec<Warning>) -> OptionalResults<'a>; 17] = [
+    canonical::Placement::names_and_times,
     ultra_signup::StatusesWithPlacements::names_and_times,
     ccr_timing::Placement::soloist_names_and_times,
     web_scorer::Placement::names_and_times,
@@ -263,13 +974,25 @@ static PARSERS: [fn(&str) -> OptionalResults; 16] = [
     race_result_mhtml::names_and_times,
 ];
 
-fn contents(p: &Path) -> Result<String> {
-    let mut file = File::open(p)?;
-    let mut bytes = Vec::new();
-    file.read_to_end(&mut bytes)?;
+// The results that list who didn't finish as well as who did.
+static DID_NOT_FINISH: [fn(&Document) -> Option<usize>; 4] = [
+    canonical::did_not_finish,
+    ultra_signup::StatusesWithPlacements::did_not_finish,
+    ancient_ultra_signup::Placement::did_not_finish,
+    ultra_signup_mhtml::StatusesWithPlacements::did_not_finish,
+];
+
+// The results that list who didn't finish by name.
+type NonFinishers = Option<Vec<NonFinisher>>;
+static NON_FINISHERS: [fn(&Document) -> NonFinishers; 3] = [
+    ultra_signup::StatusesWithPlacements::non_finishers,
+    ancient_ultra_signup::Placement::non_finishers,
+    ultra_signup_mhtml::StatusesWithPlacements::non_finishers,
+];
 
-    Ok(MessageParser::default()
-        .parse(&bytes)
+fn contents(bytes: &[u8]) -> String {
+    MessageParser::default()
+        .parse(bytes)
         .and_then(|message| {
             if message.from().is_none() {
                 None
@@ -278,20 +1001,29 @@ fn contents(p: &Path) -> Result<String> {
             }
         })
         .unwrap_or_else(|| {
-            let candidate = String::from_utf8_lossy(&bytes);
+            let candidate = String::from_utf8_lossy(bytes);
             if candidate.contains("<br/>") {
                 candidate.replace("<br/>", "\n") // for quad/2012.html
             } else {
                 candidate.into_owned()
             }
-        }))
+        })
 }
 
-fn summarize_total_times(config: &Config) -> Result<()> {
-    let mut h: HashMap<String, Vec<Option<Duration>>> = HashMap::new();
-    let n = config.results.len();
-
-    for (i, source) in config.results.iter().enumerate() {
+fn summarize_total_times(sources: &[Source], config: &Config) -> Result<()> {
+    let loader = config.loader();
+    let races = load_in_order(sources, |source| match source {
+        Source::Url(url) => Ok(RaceResults {
+            path: PathBuf::from(url.as_str()),
+            finishers: vec![],
+            warnings: vec![],
+            details: RaceDetails::default(),
+            did_not_finish: None,
+            non_finishers: vec![],
+        }),
+        Source::File(_) | Source::Combined(_) => loader.source(source),
+    })?;
+    for (source, race) in sources.iter().zip(&races) {
         match source {
             // For now, we just dump the body and don't actually use
             // it.  Of course if that body is saved into a file, we
@@ -301,27 +1033,47 @@ fn summarize_total_times(config: &Config) -> Result<()> {
                 let url = url.to_string();
                 eprintln!("url: {url}");
             }
-            Source::File(pathbuf) => {
-                let contents = contents(pathbuf)?;
-                if let Some(names_and_times) = PARSERS.iter().find_map(|parser| parser(&contents)) {
-                    // dump_ian_scores(&names_and_times);
-                    merge(&mut h, names_and_times, i, n);
-                }
-            }
+            Source::File(_) | Source::Combined(_) => loader.check(race)?,
+        }
+    }
+    let results = total_times(&races);
+    let races = sources.iter().map(Source::to_string).collect();
+    match config.output {
+        Output::Text => print_totals(&results),
+        Output::Json => write_json(&Totals { races, results })?,
+        Output::Csv | Output::Tsv => {
+            write_totals_table(config.output, config.times, races, &results)?
         }
     }
-    print(h);
     Ok(())
 }
 
+/// Totals the times of everyone who finished every one of the races,
+/// fastest total first.
+pub fn total_times(races: &[RaceResults]) -> Vec<TotalRow> {
+    let mut h: HashMap<String, Vec<Option<Duration>>> = HashMap::new();
+    let n = races.len();
+
+    for (i, race) in races.iter().enumerate() {
+        merge(&mut h, &race.finishers, i, n);
+    }
+    total_rows(h)
+}
+
 fn merge(
     h: &mut HashMap<String, Vec<Option<Duration>>>,
-    names_and_times: Vec<(Cow<str>, Duration, Option<MaleOrFemale>)>,
+    finishers: &[Finisher],
     i: usize,
     n: usize,
 ) {
-    for (name, duration, _) in names_and_times {
-        let name = names::canonical(name);
+    for Finisher {
+        name,
+        time: duration,
+        ..
+    } in finishers
+    {
+        let duration = *duration;
+        let name = names::canonical(Cow::from(name));
         match h.get_mut(name.as_ref()) {
             Some(durations) => {
                 if let Some(old_duration) = durations[i] {
@@ -344,25 +1096,56 @@ fn merge(
     }
 }
 
-fn print(all_results: HashMap<String, Vec<Option<Duration>>>) {
+#[derive(Debug, Serialize)]
+struct Totals {
+    races: Vec<String>,
+    results: Vec<TotalRow>,
+}
+
+/// Someone who finished every race, with their time in each.
+#[derive(Debug, Serialize)]
+pub struct TotalRow {
+    pub rank: usize,
+    pub name: String,
+    #[serde(serialize_with = "seconds")]
+    pub total: Duration,
+    /// in the same order as the races
+    #[serde(serialize_with = "all_seconds")]
+    pub times: Vec<Duration>,
+}
+
+fn total_rows(all_results: HashMap<String, Vec<Option<Duration>>>) -> Vec<TotalRow> {
     let mut results: Vec<_> = all_results
-        .iter()
+        .into_iter()
         .filter_map(|(name, times)| {
-            if times.iter().all(Option::is_some) {
-                let times: Vec<_> = times.iter().flatten().collect();
-                let total: Duration = times.iter().cloned().sum();
+            let times = times.into_iter().collect::<Option<Vec<_>>>()?;
+            let total: Duration = times.iter().copied().sum();
 
-                Some((total, name, times))
-            } else {
-                None
-            }
+            Some((total, name, times))
         })
         .collect();
     results.sort();
 
-    let (total_width, name_width, times_widths) = widths(&results);
+    let ranks = ranks(&results, |(total, ..)| *total);
+    results
+        .into_iter()
+        .zip(ranks)
+        .map(|((total, name, times), rank)| TotalRow {
+            rank,
+            name,
+            total,
+            times,
+        })
+        .collect()
+}
+
+fn print_totals(results: &[TotalRow]) {
+    let (total_width, name_width, times_widths) = widths(results);
 
-    for (total, name, times) in results {
+    for TotalRow {
+        name, total, times, ..
+    } in results
+    {
         print!("{name:>name_width$}");
         print!(" {total:>total_width$.1}");
         for (time, time_width) in times.iter().zip(times_widths.iter()) {
@@ -372,14 +1155,40 @@ fn print(all_results: HashMap<String, Vec<Option<Duration>>>) {
     }
 }
 
-fn widths(results: &[(Duration, &String, Vec<&Duration>)]) -> (usize, usize, Vec<usize>) {
+fn write_totals_table(
+    output: Output,
+    times: Times,
+    races: Vec<String>,
+    results: &[TotalRow],
+) -> Result<()> {
+    let mut header = vec!["rank".to_string(), "name".to_string(), "total".to_string()];
+    header.extend(races);
+    write_table(
+        output,
+        header,
+        results.iter().map(|row| {
+            let mut record = vec![
+                row.rank.to_string(),
+                row.name.clone(),
+                times.format(row.total),
+            ];
+            record.extend(row.times.iter().map(|&time| times.format(time)));
+            record
+        }),
+    )
+}
+
+fn widths(results: &[TotalRow]) -> (usize, usize, Vec<usize>) {
     use std::cmp::max;
 
-    let n_elements = results.first().map(|(_, _, v)| v.len()).unwrap_or(0);
+    let n_elements = results.first().map(|row| row.times.len()).unwrap_or(0);
     let time_widths = vec![0; n_elements];
-    results
-        .iter()
-        .fold((0, 0, time_widths), |mut triple, (total, name, times)| {
+    results.iter().fold(
+        (0, 0, time_widths),
+        |mut triple,
+         TotalRow {
+             total, name, times, ..
+         }| {
             let (ref mut total_width, ref mut name_width, ref mut times_width) = triple;
             *total_width = max(*total_width, format!("{total:.1}").len());
             *name_width = max(*name_width, name.to_string().len());
@@ -387,28 +1196,68 @@ fn widths(results: &[(Duration, &String, Vec<&Duration>)]) -> (usize, usize, Vec
                 *times_width = max(*times_width, format!("{time:.1}").len());
             }
             triple
-        })
+        },
+    )
 }
 
 #[derive(Clone, Debug)]
 enum Source {
     Url(Url),
     File(PathBuf),
+    // A race posted as several files, e.g.,
+    // FEMALE_2025_v_day_run.html@F+MALE_2025_v_day_run.html@M, each
+    // with the sex of everyone in it, if given.
+    Combined(Vec<(PathBuf, Option<MaleOrFemale>)>),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Source::Url(url) => write!(f, "{url}"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Combined(parts) => {
+                for (i, (path, sex)) in parts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "+")?;
+                    }
+                    write!(f, "{}", path.display())?;
+                    if let Some(sex) = sex {
+                        write!(f, "@{sex}")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 impl FromStr for Source {
     type Err = Error;
 
     fn from_str(arg: &str) -> Result<Self> {
-        match Url::parse(arg) {
-            Ok(url) => Ok(Source::Url(url)),
-            _ => Ok(Source::File(Path::new(arg).to_path_buf())),
+        if let Ok(url) = Url::parse(arg) {
+            return Ok(Source::Url(url));
+        }
+        // A file whose name happens to have a + or @ in it is still
+        // just a file.
+        if Path::new(arg).exists() || !arg.contains(['+', '@']) {
+            return Ok(Source::File(Path::new(arg).to_path_buf()));
         }
+        arg.split('+')
+            .map(|part| match part.rsplit_once('@') {
+                None => Ok((PathBuf::from(part), None)),
+                Some((path, sex)) => {
+                    Ok((PathBuf::from(path), Some(sex.parse().map_err(Error::msg)?)))
+                }
+            })
+            .collect::<Result<_>>()
+            .map(Source::Combined)
     }
 }
 
 #[derive(Debug, Parser)]
 #[structopt()]
+#[command(args_conflicts_with_subcommands = true)]
 /// Runs merges results from races, keeping track of who has completed
 /// all races.  The output is a set of lines, one per person, sorted
 /// by sum of that person's races.
@@ -423,42 +1272,355 @@ impl FromStr for Source {
 /// a url instead of a filename for the results, runs will not cache the
 /// results and will hit the website that is providing that result.
 pub struct Config {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// how to write the results
+    #[arg(long, value_enum, default_value_t, global = true)]
+    output: Output,
+    /// how to write times in csv and tsv output
+    #[arg(long, value_enum, default_value_t, global = true)]
+    times: Times,
+    /// fail if a results file has rows that can't be parsed cleanly
+    #[arg(long, global = true, conflicts_with = "no_warnings")]
+    strict: bool,
+    /// don't print warnings about rows that can't be parsed cleanly
+    #[arg(long, global = true)]
+    no_warnings: bool,
+    /// only use the finishers of this event or division (e.g., 10K), for
+    /// results files that have more than one; see the events subcommand
+    #[arg(long, global = true, value_name = "NAME")]
+    event: Option<String>,
+    /// parse every results file, rather than using cached results
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// how category, series and report points are scored
+    #[arg(long, value_enum, default_value_t, global = true)]
+    scoring: Method,
+    /// who each finisher is scored against
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pools: Pools,
+    /// how points are rounded
+    #[arg(long, value_enum, default_value_t, global = true)]
+    rounding: Rounding,
+    /// the points for each place with --scoring places: field,
+    /// grand-prix, a list like 25,20,16 or a table in series.toml
+    #[arg(
+        long,
+        global = true,
+        value_name = "SCHEME",
+        default_value = "grand-prix"
+    )]
+    points: Points,
+    /// where each age group starts, e.g., 20,30,40 for under 20, 20-29,
+    /// 30-39 and 40 and over, for awards and --pools division
+    #[arg(
+        long,
+        global = true,
+        value_name = "AGES",
+        default_value = "20,30,40,50,60,70,80,90"
+    )]
+    age_groups: AgeGroups,
+    /// how many results files to load at once [default: number of CPUs]
+    #[arg(long, short, global = true, value_name = "N")]
+    jobs: Option<usize>,
     /// filename or url
     results: Vec<Source>,
 }
 
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Lists each runner's longest and current consecutive-year
+    /// finishing streaks, given a directory with one results file
+    /// per year.
+    Streaks {
+        /// A year the race wasn't held (e.g., 2020), which doesn't
+        /// break anyone's streak.  May be repeated.
+        #[arg(long, value_name = "YEAR")]
+        not_held: Vec<u16>,
+        /// directory with one results file per year
+        directory: PathBuf,
+    },
+    /// Lists the finishers described by a set expression over named
+    /// groups of results files, along with their supporting times.
+    ///
+    /// Groups are combined with | (union), - (difference), & (intersection)
+    /// and atleast(K, ...), e.g.,
+    /// runs query 'cw & ccw' 'cw=assets/hrh/*[02468].mhtml' 'ccw=assets/hrh/*[13579].mhtml'
+    Query {
+        /// set expression over group names
+        expression: String,
+        /// NAME=PATTERN, where PATTERN is a file or a pattern whose last
+        /// component may contain *, ? and [...].  May be repeated to add
+        /// more files to a group.
+        #[arg(required = true, value_name = "NAME=PATTERN")]
+        groups: Vec<query::Group>,
+    },
+    /// Totals the times of everyone who finished every one of the
+    /// races, fastest total first.
+    Sum {
+        /// filename or url, or a race posted as several files, e.g.,
+        /// FEMALE.html@F+MALE.html@M
+        #[arg(required = true)]
+        results: Vec<Source>,
+    },
+    /// Scores a category: points for each race, with each runner's best
+    /// races counting.
+    Category {
+        /// directory with one results file per race
+        directory: PathBuf,
+    },
+    /// Scores a series: the best of each runner's category scores.
+    Series {
+        /// directory with one subdirectory of results files per category
+        directory: PathBuf,
+    },
+    /// Lists the finishers found in a results file, as they appear in
+    /// the file.
+    Show {
+        /// results file
+        file: PathBuf,
+    },
+    /// Lists the events or divisions in results files, any of which can
+    /// be picked with --event.
+    Events {
+        /// results files
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Ranks everyone in a directory of division files (e.g.,
+    /// bmdm-2026-solo-male-heavy.json), overall and within their
+    /// division, which is whatever follows the year in the file name.
+    Divisions {
+        /// given a directory with one subdirectory of division files per
+        /// year, list each division's finishers and winner by year
+        #[arg(long)]
+        history: bool,
+        /// directory of division files, or of years with --history
+        directory: PathBuf,
+    },
+    /// Lists every finisher's pace and speed, given results files whose
+    /// distance is known (see Race Details in the README).
+    Pace {
+        /// pace per mile or per kilometer
+        #[arg(long, value_enum, default_value_t)]
+        units: Units,
+        /// how to order the finishers
+        #[arg(long, value_enum, default_value_t)]
+        sort: pace::Sort,
+        /// results file, or a race posted as several files, e.g.,
+        /// FEMALE.html@F+MALE.html@M
+        #[arg(required = true)]
+        results: Vec<Source>,
+    },
+    /// Predicts each runner's times at standard distances from their
+    /// best result in a series, so that results at different distances
+    /// can be compared.
+    Equivalent {
+        /// how to turn a result into a time at another distance
+        #[arg(long, value_enum, default_value_t)]
+        model: equivalent::Model,
+        /// how much slower runners get as the distance grows, for the
+        /// riegel model, more than 0 and at most 2
+        #[arg(long, default_value = "1.06")]
+        exponent: equivalent::Exponent,
+        /// the distances to predict times at
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "5K,10K,half marathon,marathon"
+        )]
+        distances: Vec<equivalent::Distance>,
+        /// directory with one subdirectory of results files per category
+        directory: PathBuf,
+    },
+    /// Lists a race's awards: the fastest of each sex overall and in
+    /// each age group.
+    Awards {
+        /// how many of each sex get an overall award
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        overall: usize,
+        /// how many of each sex get an award in each age group
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        top: usize,
+        /// let overall winners also win their age group
+        #[arg(long)]
+        double: bool,
+        /// results file, or a race posted as several files, e.g.,
+        /// FEMALE.html@F+MALE.html@M
+        race: Source,
+    },
+    /// Summarizes races overall and for each sex: how many started and
+    /// finished, the winning, median, mean and percentile times and the
+    /// finishers' ages.  Given a directory with one results file per
+    /// year, each year is summarized, in order.
+    Stats {
+        /// results files, races posted as several files (e.g.,
+        /// FEMALE.html@F+MALE.html@M) or one directory with a results
+        /// file per year
+        #[arg(required = true)]
+        results: Vec<Source>,
+    },
+    /// Lists a race's all-time top performances for each sex and age
+    /// group, its course record progression and each year's winners,
+    /// given a directory with one results file per year.
+    Records {
+        /// how many performances each list has
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// directory with one results file per year
+        directory: PathBuf,
+    },
+    /// Lists every race a runner has finished, with their place, age,
+    /// personal bests and the change from one year to the next.
+    Athlete {
+        /// the runner's name, or any of their aliases
+        name: String,
+        /// results files or directories, which are searched recursively
+        #[arg(default_value = "assets")]
+        paths: Vec<PathBuf>,
+    },
+    /// Saves the finishers of a results file in runs' own format (see
+    /// the README), which reads back exactly the same.
+    Convert {
+        /// results file, or a race posted as several files, e.g.,
+        /// FEMALE.html@F+MALE.html@M
+        input: Source,
+        /// file to write, as JSON or CSV depending on its extension
+        #[arg(short, long, value_name = "OUT.json|OUT.csv")]
+        out: PathBuf,
+        /// the race's name, rather than its file name
+        #[arg(long)]
+        name: Option<String>,
+        /// the race's date, e.g., 2025-02-15
+        #[arg(long)]
+        date: Option<String>,
+        /// the race's distance, e.g., 5K or 13.1 mi
+        #[arg(long)]
+        distance: Option<String>,
+    },
+    /// Manages the cache of parsed results files.
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Writes a static HTML site for a series directory: overall
+    /// standings plus a page per category, race and runner.
+    Report {
+        /// directory to write the pages into
+        #[arg(long, value_name = "OUTDIR")]
+        html: PathBuf,
+        /// directory with one subdirectory of results files per category
+        series: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommand {
+    /// Removes results cached by older versions of the parsers.
+    Prune {
+        /// remove every cached result
+        #[arg(long)]
+        all: bool,
+    },
+}
+
 impl Config {
     pub fn new() -> Result<Self> {
         Ok(Config::try_parse_from(std::env::args())?)
     }
+
+    fn scoring(&self) -> Scoring {
+        Scoring {
+            method: self.scoring,
+            pools: self.pools,
+            rounding: self.rounding,
+            points: self.points.clone(),
+            age_groups: self.age_groups.clone(),
+        }
+    }
+
+    fn loader(&self) -> Loader {
+        Loader {
+            // The cache is best effort, so not being able to find a
+            // place for it isn't an error.
+            cache: if self.no_cache {
+                None
+            } else {
+                Cache::open().ok()
+            },
+            on_warning: if self.strict {
+                OnWarning::Fail
+            } else if self.no_warnings {
+                OnWarning::Ignore
+            } else {
+                OnWarning::Print
+            },
+            event: self.event.clone(),
+        }
+    }
 }
 
-// This code never should have gone into master, but I wound up doing
-// a bunch of work in my for-ian branch that really should have been
-// done elsewhere.  However, I had a soft deadline to get for-ian
-// done, so I didn't care.  Then covid hit and that soft deadline
-// disappeared, so rather than finish for-ian, I simply did some
-// minimal squashing and tossed it in.
-#[allow(dead_code)]
-fn dump_ian_scores(names_and_times: &[(Cow<str>, Duration)]) {
-    let mut names_and_times = names_and_times.to_vec();
-
-    names_and_times.sort_by_key(|&(_, time)| time);
-    let n = names_and_times.len();
-    let half_n = n / 2;
-    let median = if n % 2 == 1 {
-        names_and_times[half_n].1
-    } else {
-        (names_and_times[half_n - 1].1 + names_and_times[half_n].1) / 2
-    };
-    let median: f64 = median.into();
-    for (name, time) in names_and_times {
-        let time: f64 = time.as_secs() as f64;
-        println!("{:>3}: {}", (median / time * 100.0).floor(), name);
+// How the command line loads results files: through the cache, unless
+// --no-cache was given, with only the finishers of the --event, if
+// any, and with warnings handled the way --strict and --no-warnings say.
+pub(crate) struct Loader {
+    cache: Option<Cache>,
+    on_warning: OnWarning,
+    event: Option<String>,
+}
+
+impl Loader {
+    pub(crate) fn results(&self, path: &Path) -> Result<RaceResults> {
+        self.select(self.all_results(path)?)
+    }
+
+    // Only the --event's finishers, if there is one.
+    pub(crate) fn select(&self, race: RaceResults) -> Result<RaceResults> {
+        match &self.event {
+            None => Ok(race),
+            Some(event) => race.select_event(event),
+        }
+    }
+
+    // Every event's finishers, regardless of --event.
+    pub(crate) fn all_results(&self, path: &Path) -> Result<RaceResults> {
+        match &self.cache {
+            None => load_results(path),
+            Some(cache) => cache.load_results(path),
+        }
+    }
+
+    // A results file, or a race posted as several files.
+    fn source(&self, source: &Source) -> Result<RaceResults> {
+        match source {
+            Source::Url(url) => bail!("Can't load {url}; save it to a file first"),
+            Source::File(path) => self.results(path),
+            Source::Combined(parts) => {
+                let parts = parts
+                    .iter()
+                    .map(|(path, sex)| Ok((self.results(path)?, *sex)))
+                    .collect::<Result<Vec<_>>>()?;
+                // parts is never empty; see Source::from_str.
+                Ok(combine_results(parts).unwrap())
+            }
+        }
+    }
+
+    // Unlike load_category, hidden files and subdirectories are skipped.
+    fn category(&self, directory: &Path) -> Result<Category> {
+        visible_category(directory, &|path: &Path| self.results(path))
+    }
+
+    pub(crate) fn series(&self, directory: &Path) -> Result<Vec<Category>> {
+        visible_series(directory, &|path: &Path| self.results(path))
+    }
+
+    pub(crate) fn check(&self, race: &RaceResults) -> Result<()> {
+        self.on_warning.check(race)
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum MaleOrFemale {
     Male = 0,
     Female = 1,
@@ -498,8 +1660,9 @@ pub(crate) trait Gender {
     fn gender(&self) -> &str;
 }
 
-pub(crate) trait Morf: Gender {
-    fn morf(&self) -> Option<MaleOrFemale> {
+pub(crate) trait Morf: Gender + Debug {
+    // number is which row self is (see warning.rs).
+    fn morf(&self, number: usize, warnings: &mut Vec<Warning>) -> Option<MaleOrFemale> {
         use MaleOrFemale::*;
 
         match self.gender() {
@@ -507,16 +1670,253 @@ pub(crate) trait Morf: Gender {
             "F" | "Female" => Some(Female),
             "X" | "O" => Some(NonBinary),
             "U" | "" => None,
-            other => panic!("Unknown gender: {other}"),
+            other => {
+                warnings.push(Warning::UnknownGender {
+                    gender: other.to_string(),
+                    number,
+                    row: format!("{self:?}"),
+                });
+                None
+            }
         }
     }
 }
 
-impl<T: Gender> Morf for T {}
+impl<T: Gender + Debug> Morf for T {}
 
-pub(crate) type OptionalResults<'a> = Option<Vec<(Cow<'a, str>, Duration, Option<MaleOrFemale>)>>;
+// name, time, sex, the event or division the finisher is listed
+// under, for files that have more than one, and the pace and age, for
+// files that list them.
+pub(crate) type OptionalResults<'a> = Option<
+    Vec<(
+        Cow<'a, str>,
+        Duration,
+        Option<MaleOrFemale>,
+        Option<Cow<'a, str>>,
+        Option<Duration>,
+        Option<u8>,
+    )>,
+>;
 
 pub(crate) mod prelude {
-    pub(crate) use super::{Gender, MaleOrFemale, Morf, OptionalResults};
+    pub(crate) use super::{
+        Document, Gender, MaleOrFemale, Morf, NonFinisher, OptionalResults, Warning,
+    };
     pub(crate) use std::borrow::Cow;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn race(name: &str, finishers: &[(&str, u64, MaleOrFemale)]) -> RaceResults {
+        RaceResults {
+            path: PathBuf::from(format!("{name}.json")),
+            finishers: finishers
+                .iter()
+                .map(|&(name, secs, morf)| Finisher {
+                    name: name.to_string(),
+                    time: Duration::new(secs, 0),
+                    morf: Some(morf),
+                    event: None,
+                    pace: None,
+                    age: None,
+                })
+                .collect(),
+            warnings: vec![],
+            details: RaceDetails::default(),
+            did_not_finish: None,
+            non_finishers: vec![],
+        }
+    }
+
+    #[derive(Debug)]
+    struct Row(&'static str);
+
+    impl Gender for Row {
+        fn gender(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_morf() {
+        let mut warnings = vec![];
+        assert_eq!(Row("F").morf(1, &mut warnings), Some(MaleOrFemale::Female));
+        assert_eq!(Row("U").morf(2, &mut warnings), None);
+        assert!(warnings.is_empty());
+        assert_eq!(Row("Q").morf(3, &mut warnings), None);
+        assert_eq!(
+            warnings,
+            [Warning::UnknownGender {
+                gender: "Q".to_string(),
+                number: 3,
+                row: "Row(\"Q\")".to_string(),
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "unknown gender \"Q\" in row 3: Row(\"Q\")"
+        );
+    }
+
+    #[test]
+    fn test_total_times() {
+        use MaleOrFemale::*;
+
+        let races = [
+            race("a", &[("Ann", 100, Female), ("Bob", 90, Male)]),
+            race("b", &[("Bob", 200, Male), ("Cal", 150, Male)]),
+        ];
+        let totals = total_times(&races);
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].name, "Bob");
+        assert_eq!(totals[0].total, Duration::new(290, 0));
+    }
+
+    #[test]
+    fn test_score_category() {
+        use MaleOrFemale::*;
+
+        let races = [
+            race("a", &[("Ann", 100, Female), ("Bob", 90, Male)]),
+            race("b", &[("Bob", 200, Male), ("Cal", 100, Male)]),
+        ];
+        let rows = score_category(&races);
+        let points = rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.points, row.count, row.race.as_str()))
+            .collect::<Vec<_>>();
+        assert!(points.contains(&("Ann", 100, 1, "a")));
+        assert!(points.contains(&("Bob", 100, 2, "a")));
+        assert!(points.contains(&("Cal", 100, 1, "b")));
+        assert!(rows.iter().all(|row| row.rank == 1));
+    }
+
+    #[test]
+    fn test_score_category_aliases() {
+        use MaleOrFemale::*;
+
+        // Cliff Matthews and Deadhead are both aliases of Clifford
+        // Matthews, whose races should all count.
+        let races = [
+            race("a", &[("Cliff Matthews", 90, Male), ("Bob", 100, Male)]),
+            race("b", &[("Bob", 100, Male), ("Deadhead", 200, Male)]),
+        ];
+        let rows = score_category(&races);
+        let row = rows
+            .iter()
+            .find(|row| row.name == "Clifford Matthews")
+            .unwrap();
+        assert_eq!((row.points, row.count, row.race.as_str()), (100, 2, "a"));
+    }
+
+    #[test]
+    fn test_combine_results() {
+        use MaleOrFemale::*;
+
+        let mut women = race("women", &[("Ann", 100, Female)]);
+        women.finishers[0].morf = None;
+        let men = race("men", &[("Bob", 90, Male)]);
+        let combined = combine_results([(women, Some(Female)), (men, Some(Female))]).unwrap();
+        assert_eq!(combined.path, PathBuf::from("women.json"));
+        let finishers = combined
+            .finishers
+            .iter()
+            .map(|finisher| (finisher.name.as_str(), finisher.morf))
+            .collect::<Vec<_>>();
+        // The sex a file gives wins over the sex it's tagged with.
+        assert_eq!(finishers, [("Ann", Some(Female)), ("Bob", Some(Male))]);
+        assert!(combine_results([]).is_none());
+    }
+
+    #[test]
+    fn test_select_event() {
+        use MaleOrFemale::*;
+
+        let mut results = race(
+            "a",
+            &[("Ann", 100, Female), ("Bob", 90, Male), ("Cal", 80, Male)],
+        );
+        for (finisher, event) in results.finishers.iter_mut().zip(["5K", "10K", "5K"]) {
+            finisher.event = Some(event.to_string());
+        }
+        assert_eq!(results.events(), ["5K", "10K"]);
+        let five_k = results.clone().select_event("5k").unwrap();
+        let names = five_k
+            .finishers
+            .iter()
+            .map(|finisher| finisher.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Ann", "Cal"]);
+        assert!(results.select_event("Half").is_err());
+        assert!(race("b", &[]).select_event("5K").is_err());
+    }
+
+    #[test]
+    fn test_combined_source() {
+        let source = Source::from_str("women.html@F+men.html").unwrap();
+        assert!(matches!(
+            &source,
+            Source::Combined(parts) if parts == &[
+                (PathBuf::from("women.html"), Some(MaleOrFemale::Female)),
+                (PathBuf::from("men.html"), None),
+            ]
+        ));
+        assert_eq!(source.to_string(), "women.html@F+men.html");
+        assert!(Source::from_str("women.html@W").is_err());
+        assert!(matches!(
+            Source::from_str("women.html"),
+            Ok(Source::File(_))
+        ));
+    }
+
+    #[test]
+    fn test_visible_entries() {
+        let directory =
+            std::env::temp_dir().join(format!("runs-entries-test-{}", std::process::id()));
+        fs::create_dir_all(directory.join("10k")).unwrap();
+        fs::create_dir_all(directory.join(".git")).unwrap();
+        fs::write(directory.join("NOTE"), "").unwrap();
+        fs::write(directory.join(".DS_Store"), "").unwrap();
+
+        let directories = visible_entries(&directory, true).unwrap();
+        let files = visible_entries(&directory, false).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(directories, [directory.join("10k")]);
+        assert_eq!(files, [directory.join("NOTE")]);
+    }
+
+    #[test]
+    fn test_load_series_skips_strays() {
+        let directory =
+            std::env::temp_dir().join(format!("runs-series-test-{}", std::process::id()));
+        let category = directory.join("50k");
+        fs::create_dir_all(&category).unwrap();
+        fs::copy("assets/mt_taylor_50k/2019.json", category.join("2019.json")).unwrap();
+        fs::write(category.join(".DS_Store"), "").unwrap();
+        fs::write(directory.join("NOTE"), "").unwrap();
+
+        let series = load_series(&directory);
+        fs::remove_dir_all(&directory).unwrap();
+        let series = series.unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].name, "50k");
+        assert_eq!(series[0].races.len(), 1);
+    }
+
+    #[test]
+    fn test_config_args() {
+        // Options go after the subcommand.  Once there's an argument
+        // before it, the rest are results files, whatever they're named.
+        let config = Config::try_parse_from(["runs", "-j", "2", "a.json", "stats"]).unwrap();
+        assert!(config.command.is_none());
+        assert_eq!(config.jobs, Some(2));
+        assert_eq!(config.results.len(), 2);
+
+        let config = Config::try_parse_from(["runs", "stats", "-j", "2", "a.json"]).unwrap();
+        assert!(matches!(config.command, Some(Command::Stats { results }) if results.len() == 1));
+        assert_eq!(config.jobs, Some(2));
+        assert!(config.results.is_empty());
+    }
+}
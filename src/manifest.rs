@@ -0,0 +1,205 @@
+// A series directory is normally just one subdirectory per category
+// and one results file per race, but real series directories collect
+// other things too: notes, the separate male and female pages that a
+// race's overall page was built from, and so on.  Rather than rely on
+// glob tricks to leave those out, a series directory can have a
+// series.toml that lists its races explicitly, e.g.,
+//
+//     [[race]]
+//     category = "2024-2025"
+//     name = "V Day Run"
+//     date = "2025-02-15"
+//     distance = "5K"
+//     surface = "road"
+//     event = "5K"   # for files with more than one event or division
+//     files = [
+//         { path = "2024-2025/FEMALE_2025_v_day_run.html", sex = "F" },
+//         { path = "2024-2025/MALE_2025_v_day_run.html", sex = "M" },
+//     ]
+//
+// Only the listed files are loaded.  Files are relative to the
+// directory that has the series.toml.  A race listed with more than
+// one file (e.g., one page per sex) has the finishers of all of them.
+// A file can be given as just its path, or with the sex of everyone in
+// it, for pages that don't otherwise say, and/or the division everyone
+// in it is in, e.g.,
+//
+//     { path = "2026/bmdm-2026-solo-male-heavy.json", division = "solo-male-heavy" }
+//
+// A race can have any of the details that a results file's sidecar can
+// (see details.rs), and they win over the sidecar's.  Categories come in
+// the order they first appear, and races within a category in the order
+// they're listed.
+//
+// A series that scores by place (see scoring.rs) can name its own points
+// tables, e.g.,
+//
+//     [points]
+//     club = [25, 20, 16, 13, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]
+//
+// which --points club then picks.
+
+use {
+    crate::{
+        Category, MaleOrFemale, RaceDetails, RaceResults, Surface, Units, combine_results,
+        details::number_or_string, divisions, load_in_order,
+    },
+    anyhow::{Context, Error, Result, bail},
+    serde::Deserialize,
+    std::{
+        collections::HashMap,
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+pub(crate) const FILE_NAME: &str = "series.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(rename = "race", default)]
+    races: Vec<Race>,
+    /// points for first, second, and so on, by the table's name
+    #[serde(default)]
+    points: HashMap<String, Vec<u16>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Race {
+    category: String,
+    /// what the race is called in the output, rather than its file name
+    name: Option<String>,
+    /// e.g., 2025-02-15
+    date: Option<String>,
+    /// e.g., 5K or 13.1 mi
+    #[serde(default, deserialize_with = "number_or_string")]
+    distance: Option<String>,
+    units: Option<Units>,
+    surface: Option<Surface>,
+    course: Option<String>,
+    /// only the finishers of this event or division
+    event: Option<String>,
+    files: Vec<File>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum File {
+    Path(PathBuf),
+    Tagged {
+        path: PathBuf,
+        sex: Option<String>,
+        division: Option<String>,
+    },
+}
+
+impl File {
+    fn path(&self) -> &Path {
+        match self {
+            File::Path(path) | File::Tagged { path, .. } => path,
+        }
+    }
+
+    fn sex(&self) -> Result<Option<MaleOrFemale>> {
+        match self {
+            File::Tagged { sex: Some(sex), .. } => Ok(Some(sex.parse().map_err(Error::msg)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn division(&self) -> Option<&str> {
+        match self {
+            File::Tagged { division, .. } => division.as_deref(),
+            File::Path(_) => None,
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Option<Manifest>> {
+    let text = match fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        text => text?,
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .with_context(|| format!("Can't read {}", path.display()))
+}
+
+// The points tables in directory's series.toml, which are none if it
+// doesn't have one.
+pub(crate) fn points_tables(directory: &Path) -> Result<HashMap<String, Vec<u16>>> {
+    Ok(read(&directory.join(FILE_NAME))?
+        .map(|manifest| manifest.points)
+        .unwrap_or_default())
+}
+
+// The series described by directory's series.toml, or None if it
+// doesn't have one.
+pub(crate) fn load(
+    directory: &Path,
+    load: impl Fn(&Path) -> Result<RaceResults> + Sync + Send,
+) -> Result<Option<Vec<Category>>> {
+    let path = directory.join(FILE_NAME);
+    let Some(manifest) = read(&path)? else {
+        return Ok(None);
+    };
+    if let Some(race) = manifest.races.iter().find(|race| race.files.is_empty()) {
+        bail!("{}: no files for {race:?}", path.display());
+    }
+
+    let files = manifest.races.iter().flat_map(|race| &race.files);
+    let sexes = files
+        .clone()
+        .map(File::sex)
+        .collect::<Result<Vec<_>>>()
+        .with_context(|| format!("Can't read {}", path.display()))?;
+    let paths = files
+        .clone()
+        .map(|file| directory.join(file.path()))
+        .collect::<Vec<_>>();
+    let mut parts = load_in_order(paths, |path| load(&path))?
+        .into_iter()
+        .zip(files.clone())
+        .zip(sexes)
+        .map(|((mut results, file), sex)| {
+            if let Some(division) = file.division() {
+                divisions::label(&mut results, division);
+            }
+            (results, sex)
+        })
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    let mut categories: Vec<Category> = vec![];
+    for race in manifest.races {
+        // files isn't empty, so there's always at least one part.
+        let mut combined = combine_results(parts.by_ref().take(race.files.len())).unwrap();
+        if let Some(event) = &race.event {
+            combined = combined.select_event(event)?;
+        }
+        // What the series.toml says about a race wins over what its
+        // (first) file says.
+        combined.details = RaceDetails {
+            name: race.name,
+            date: race.date,
+            distance: race.distance,
+            units: race.units,
+            surface: race.surface,
+            course: race.course,
+        }
+        .or(combined.details);
+        match categories
+            .iter_mut()
+            .find(|category| category.name == race.category)
+        {
+            Some(category) => category.races.push(combined),
+            None => categories.push(Category {
+                name: race.category,
+                races: vec![combined],
+            }),
+        }
+    }
+    Ok(Some(categories))
+}
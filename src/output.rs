@@ -0,0 +1,136 @@
+// Every mode builds its results as a list of rows before anything is
+// written, so that the same rows can be written as padded text (for
+// people), as JSON (for programs, e.g., the club web site) or as CSV
+// or TSV (for spreadsheets).
+
+use {
+    ::csv::WriterBuilder,
+    anyhow::Result,
+    clap::ValueEnum,
+    digital_duration_nom::duration::Duration,
+    serde::{Serialize, Serializer},
+    std::io::{self, Write},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Output {
+    /// padded columns
+    #[default]
+    Text,
+    /// a single JSON document
+    Json,
+    /// comma separated values, one row per person
+    Csv,
+    /// tab separated values, one row per person
+    Tsv,
+}
+
+// How times are written in CSV and TSV output.  Unlike the text
+// output, hours are always present, so that every time in a column
+// looks the same.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Times {
+    /// H:MM:SS.s
+    #[default]
+    Hms,
+    /// seconds, to the tenth
+    Seconds,
+}
+
+impl Times {
+    pub(crate) fn format(self, duration: Duration) -> String {
+        let secs = duration.as_secs();
+        let tenths = duration.subsec_millis() / 100;
+        match self {
+            Times::Hms => format!(
+                "{}:{:02}:{:02}.{tenths}",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60
+            ),
+            Times::Seconds => format!("{secs}.{tenths}"),
+        }
+    }
+}
+
+// Writes a header and rows as CSV or TSV.  Missing values are empty
+// strings, which become blank cells.
+pub(crate) fn write_table(
+    output: Output,
+    header: Vec<String>,
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> Result<()> {
+    let delimiter = if output == Output::Tsv { b'\t' } else { b',' };
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(io::stdout().lock());
+    writer.write_record(header)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn write_json(value: &impl Serialize) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+// Durations are written as (fractional) seconds, since that's what's
+// easiest for other programs to work with.
+pub(crate) fn seconds<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64((*duration).into())
+}
+
+pub(crate) fn optional_seconds<S: Serializer>(
+    duration: &Option<Duration>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        None => s.serialize_none(),
+        Some(duration) => seconds(duration, s),
+    }
+}
+
+pub(crate) fn all_seconds<S: Serializer>(durations: &[Duration], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(durations.iter().map(|&duration| f64::from(duration)))
+}
+
+// Standard competition ranking ("1224"), where everyone with the same
+// key as the person before them gets the same rank.  The items must
+// already be sorted by key.
+pub(crate) fn ranks<T, K: PartialEq>(items: &[T], key: impl Fn(&T) -> K) -> Vec<usize> {
+    let mut ranks: Vec<usize> = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let rank = match ranks.last() {
+            Some(&rank) if key(&items[i - 1]) == key(item) => rank,
+            _ => i + 1,
+        };
+        ranks.push(rank);
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_times() {
+        let duration = Duration::new(23 * 60 + 53, 480_000_000);
+        assert_eq!(Times::Hms.format(duration), "0:23:53.4");
+        assert_eq!(Times::Seconds.format(duration), "1433.4");
+    }
+
+    #[test]
+    fn test_ranks() {
+        assert_eq!(
+            ranks(&[100, 98, 98, 97, 90, 90, 90, 2], |&p| p),
+            [1, 2, 2, 4, 5, 5, 5, 8]
+        );
+        assert!(ranks(&[] as &[u16], |&p| p).is_empty());
+    }
+}
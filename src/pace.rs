@@ -0,0 +1,265 @@
+// Given a race's distance (see details.rs), every finisher's pace and
+// speed can be computed, whether or not the results list a pace (most
+// don't).  Pace is what makes finishers of races of different distances
+// comparable, so the pace subcommand can sort everyone in several races
+// by it.
+//
+// When the results do list a pace, it's checked against the computed
+// one, since paces that don't agree are the easiest way to catch a race
+// whose distance is wrong, e.g., a 10K whose sidecar says 5K.  Results
+// rarely say whether their paces are per mile or per kilometer, so a
+// listed pace agrees if it's close to either.
+
+use {
+    crate::{
+        Duration, Loader, RaceResults, Source, Units, Warning,
+        details::METERS_PER_MILE,
+        load_in_order,
+        output::{Output, Times, optional_seconds, seconds, write_json, write_table},
+    },
+    anyhow::{Result, anyhow},
+    clap::ValueEnum,
+    serde::Serialize,
+};
+
+// How far a listed pace can be from the computed one and still agree.
+// Listed paces are rounded to the second and are sometimes from gun
+// time rather than chip time.
+const TOLERANCE: f64 = 0.03;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub(crate) enum Sort {
+    /// fastest pace first
+    #[default]
+    Pace,
+    /// fastest time first
+    Time,
+    /// by name, then pace
+    Name,
+}
+
+fn unit_meters(units: Units) -> f64 {
+    match units {
+        Units::Km => 1000.0,
+        Units::Mi => METERS_PER_MILE,
+    }
+}
+
+// Time per mile or kilometer, unless the distance is so short that
+// it's too big for a Duration.
+pub(crate) fn pace(time: Duration, meters: f64, units: Units) -> Option<Duration> {
+    from_secs(time.as_secs_f64() * unit_meters(units) / meters)
+}
+
+// A computed number of seconds as a Duration, unless it's negative,
+// NaN or too big for one.
+pub(crate) fn from_secs(secs: f64) -> Option<Duration> {
+    let duration = std::time::Duration::try_from_secs_f64(secs).ok()?;
+    Some(Duration::new(duration.as_secs(), duration.subsec_nanos()))
+}
+
+// Miles or kilometers per hour.
+pub(crate) fn speed(time: Duration, meters: f64, units: Units) -> f64 {
+    meters / unit_meters(units) / (time.as_secs_f64() / 3600.0)
+}
+
+// A warning if fewer than half of the paces that the results list
+// agree with the race's distance.
+pub(crate) fn check(race: &RaceResults) -> Option<Warning> {
+    let meters = race.details.meters()?;
+    let (mut agreeing, mut paces) = (0, 0);
+    for finisher in &race.finishers {
+        if let Some(listed) = finisher.pace {
+            paces += 1;
+            if agrees(listed, finisher.time, meters) {
+                agreeing += 1;
+            }
+        }
+    }
+    (agreeing * 2 < paces).then(|| Warning::DistanceMismatch {
+        distance: race.details.distance.clone().unwrap_or_default(),
+        agreeing,
+        paces,
+    })
+}
+
+fn agrees(listed: Duration, time: Duration, meters: f64) -> bool {
+    [Units::Mi, Units::Km].into_iter().any(|units| {
+        pace(time, meters, units).is_some_and(|computed| {
+            let computed = computed.as_secs_f64();
+            (listed.as_secs_f64() - computed).abs() <= computed * TOLERANCE
+        })
+    })
+}
+
+#[derive(Serialize)]
+struct Row {
+    name: String,
+    race: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+    #[serde(serialize_with = "seconds")]
+    pace: Duration,
+    speed: f64,
+    /// the pace the results list, if any
+    #[serde(serialize_with = "optional_seconds")]
+    listed_pace: Option<Duration>,
+}
+
+pub(crate) fn summarize(
+    sources: &[Source],
+    units: Units,
+    sort: Sort,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let races = load_in_order(sources, |source| loader.source(source))?;
+    let mut rows = vec![];
+    for race in &races {
+        loader.check(race)?;
+        let meters = race.details.meters().ok_or_else(|| {
+            anyhow!(
+                "{} doesn't have a distance (or it isn't more than 0); give it a sidecar with one",
+                race.path.display()
+            )
+        })?;
+        rows.extend(race.finishers.iter().filter_map(|finisher| {
+            let Some(pace) = pace(finisher.time, meters, units) else {
+                eprintln!(
+                    "Can't compute {}'s pace in {}, skipping",
+                    finisher.name,
+                    race.path.display()
+                );
+                return None;
+            };
+            Some(Row {
+                name: finisher.name.clone(),
+                race: race.name(),
+                time: finisher.time,
+                pace,
+                speed: speed(finisher.time, meters, units),
+                listed_pace: finisher.pace,
+            })
+        }));
+    }
+    match sort {
+        Sort::Pace => rows.sort_by_key(|row| row.pace),
+        Sort::Time => rows.sort_by_key(|row| row.time),
+        Sort::Name => rows.sort_by(|r1, r2| (&r1.name, r1.pace).cmp(&(&r2.name, r2.pace))),
+    }
+
+    let per_hour = match units {
+        Units::Km => "km/h",
+        Units::Mi => "mph",
+    };
+    match output {
+        Output::Text => {
+            let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+            for row in &rows {
+                println!(
+                    "{:name_width$} {:>10.1} {:>7}/{units} {:5.2} {per_hour} {}",
+                    row.name,
+                    row.time,
+                    minutes_and_seconds(row.pace),
+                    row.speed,
+                    row.race
+                );
+            }
+        }
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            vec![
+                "name".to_string(),
+                "race".to_string(),
+                "time".to_string(),
+                format!("pace per {units}"),
+                per_hour.to_string(),
+                "listed pace".to_string(),
+            ],
+            rows.iter().map(|row| {
+                vec![
+                    row.name.clone(),
+                    row.race.clone(),
+                    times.format(row.time),
+                    times.format(row.pace),
+                    format!("{:.2}", row.speed),
+                    row.listed_pace
+                        .map(|pace| times.format(pace))
+                        .unwrap_or_default(),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+// e.g., 7:45, rounded to the second.
+fn minutes_and_seconds(pace: Duration) -> String {
+    let secs = pace.as_secs_f64().round() as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::RaceDetails};
+
+    #[test]
+    fn test_pace() {
+        let time = Duration::new(20 * 60, 0);
+        assert_eq!(pace(time, 5000.0, Units::Km), Some(Duration::new(240, 0)));
+        assert_eq!(
+            minutes_and_seconds(pace(time, 5000.0, Units::Mi).unwrap()),
+            "6:26"
+        );
+        assert_eq!(pace(time, 1e-300, Units::Km), None);
+        assert!((speed(time, 5000.0, Units::Km) - 15.0).abs() < 1e-9);
+        assert_eq!(
+            minutes_and_seconds(Duration::new(599, 600_000_000)),
+            "10:00"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let finisher = |secs, listed| crate::Finisher {
+            name: "Jane Doe".to_string(),
+            time: Duration::new(secs, 0),
+            morf: None,
+            event: None,
+            pace: Some(Duration::new(listed, 0)),
+            age: None,
+        };
+        let mut race = RaceResults {
+            path: "5k.html".into(),
+            // per mile, per kilometer and per mile from gun time
+            finishers: vec![
+                finisher(1200, 386),
+                finisher(1500, 300),
+                finisher(1800, 585),
+            ],
+            warnings: vec![],
+            details: RaceDetails {
+                distance: Some("5K".to_string()),
+                ..RaceDetails::default()
+            },
+            did_not_finish: None,
+            non_finishers: vec![],
+        };
+        assert_eq!(check(&race), None);
+
+        race.details.distance = Some("10K".to_string());
+        assert_eq!(
+            check(&race),
+            Some(Warning::DistanceMismatch {
+                distance: "10K".to_string(),
+                agreeing: 0,
+                paces: 3
+            })
+        );
+
+        race.details.distance = None;
+        assert_eq!(check(&race), None);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod ancient_ultra_signup;
 pub mod athlinks;
+pub mod canonical;
 pub mod ccr_timing;
 pub mod chrono_track;
 pub mod csv;
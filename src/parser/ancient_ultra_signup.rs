@@ -1,8 +1,9 @@
 use {
     crate::prelude::*, digital_duration_nom::duration::Duration, serde::Deserialize,
-    std::str::FromStr,
+    serde_json::Value, std::str::FromStr,
 };
 
+#[derive(Debug)]
 pub enum Status {
     Finished = 1,
     DidNotFinish = 2,
@@ -39,6 +40,7 @@ struct PlacementJson {
 }
 
 #[allow(dead_code)]
+#[derive(Debug)]
 pub struct Placement {
     pub age: u8,             // 30
     pub age_rank: u16,       // 0
@@ -55,34 +57,92 @@ pub struct Placement {
     pub state: String,       // "AZ"
     pub status: Status,
     pub time: Duration,
-    pub name: String, // This one we create ourselves
+    pub name: String,  // This one we create ourselves
+    pub number: usize, // As is this, which row it is (see warning.rs)
 }
 
 impl Placement {
-    pub fn results(contents: &str) -> Option<Vec<Self>> {
-        let json: Result<Vec<PlacementJson>, serde_json::error::Error> =
-            serde_json::from_str(contents);
-        match json {
-            Ok(json) => Some(json.into_iter().map(Self::from).collect()),
+    pub fn results(json: &Value, warnings: &mut Vec<Warning>) -> Option<Vec<Self>> {
+        match Vec::<PlacementJson>::deserialize(json) {
+            Ok(json) => Some(
+                json.into_iter()
+                    .zip(1..)
+                    .filter_map(|(json, number)| {
+                        Self::new(json, number).map_err(|w| warnings.push(w)).ok()
+                    })
+                    .collect(),
+            ),
             Err(_) => None,
         }
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
         use Status::*;
 
-        Self::results(input).map(|results| {
+        document
+            .json()
+            .and_then(|json| Self::results(json, warnings))
+            .map(|results| {
+                results
+                    .into_iter()
+                    .filter_map(|placement| match placement.status {
+                        Finished => {
+                            let morf = placement.morf(placement.number, warnings);
+                            Some((
+                                Cow::from(placement.name),
+                                placement.time,
+                                morf,
+                                None,
+                                None,
+                                (placement.age > 0).then_some(placement.age),
+                            ))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+    }
+
+    // Everyone who started but didn't finish, including those who were
+    // disqualified.
+    pub fn did_not_finish(document: &Document) -> Option<usize> {
+        use Status::*;
+
+        let results = document
+            .json()
+            .and_then(|json| Self::results(json, &mut vec![]))?;
+        Some(
+            results
+                .iter()
+                .filter(|placement| matches!(placement.status, DidNotFinish | Disqualified))
+                .count(),
+        )
+    }
+
+    pub fn non_finishers(document: &Document) -> Option<Vec<NonFinisher>> {
+        let results = document
+            .json()
+            .and_then(|json| Self::results(json, &mut vec![]))?;
+        Some(
             results
                 .into_iter()
-                .filter_map(|placement| match placement.status {
-                    Finished => {
-                        let morf = placement.morf();
-                        Some((Cow::from(placement.name), placement.time, morf))
-                    }
-                    _ => None,
+                .filter_map(|placement| {
+                    let status = match placement.status {
+                        Status::Finished => return None,
+                        Status::DidNotFinish => crate::Status::DidNotFinish,
+                        Status::DidNotStart => crate::Status::DidNotStart,
+                        Status::Disqualified => crate::Status::Disqualified,
+                    };
+                    Some(NonFinisher {
+                        name: placement.name,
+                        status,
+                    })
                 })
-                .collect()
-        })
+                .collect(),
+        )
     }
 }
 
@@ -92,8 +152,9 @@ impl Gender for Placement {
     }
 }
 
-impl From<PlacementJson> for Placement {
-    fn from(json: PlacementJson) -> Self {
+impl Placement {
+    // number is which row json is, from 1.
+    fn new(json: PlacementJson, number: usize) -> Result<Self, Warning> {
         use Status::*;
 
         const FINISHED: u8 = Finished as u8;
@@ -101,6 +162,7 @@ impl From<PlacementJson> for Placement {
         const DID_NOT_START: u8 = DidNotStart as u8;
         const DISQUALIFIED: u8 = Disqualified as u8;
 
+        let row = format!("{json:?}");
         let age = json.age;
         let age_rank = json.age_rank;
         let age_group = json.agegroup;
@@ -119,17 +181,28 @@ impl From<PlacementJson> for Placement {
             DID_NOT_FINISH => DidNotFinish,
             DID_NOT_START => DidNotStart,
             DISQUALIFIED => Disqualified,
-            other => panic!("Unknown status {other}"),
+            other => {
+                return Err(Warning::UnknownStatus {
+                    status: other,
+                    number,
+                    row,
+                });
+            }
         };
 
-        let milliseconds = u64::from_str(&json.time).unwrap();
+        let milliseconds = u64::from_str(&json.time).map_err(|e| Warning::BadField {
+            field: "time".to_string(),
+            error: e.to_string(),
+            number,
+            row,
+        })?;
         let secs = milliseconds / 1000;
         let nanos = (milliseconds % 1000) as u32 * 1_000_000;
         let time = Duration::new(secs, nanos);
 
         let name = format!("{first_name} {last_name}");
 
-        Self {
+        Ok(Self {
             age,
             age_rank,
             age_group,
@@ -146,6 +219,7 @@ impl From<PlacementJson> for Placement {
             status,
             time,
             name,
-        }
+            number,
+        })
     }
 }
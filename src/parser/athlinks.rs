@@ -2,6 +2,7 @@ use {
     crate::prelude::*,
     digital_duration_nom::duration::Duration,
     serde::Deserialize,
+    serde_json::Value,
     std::num::{NonZeroU8, NonZeroU16},
 };
 
@@ -21,26 +22,39 @@ pub struct Placement {
 }
 
 impl Placement {
-    pub fn results(contents: &str) -> Option<Vec<Self>> {
-        contents
-            .trim()
-            .split('\n')
-            .map(serde_json::from_str::<Vec<_>>)
+    pub fn results(lines: &[Value]) -> Option<Vec<Self>> {
+        lines
+            .iter()
+            .map(Vec::<Self>::deserialize)
             .collect::<Result<Vec<_>, _>>()
             .ok()
             .map(|v| v.into_iter().flatten().collect())
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
-            results
-                .into_iter()
-                .map(|placement| {
-                    let morf = placement.morf();
-                    (Cow::from(placement.name), placement.time, morf)
-                })
-                .collect()
-        })
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        document
+            .json_lines()
+            .and_then(Self::results)
+            .map(|results| {
+                results
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, placement)| {
+                        let morf = placement.morf(i + 1, warnings);
+                        (
+                            Cow::from(placement.name),
+                            placement.time,
+                            morf,
+                            None,
+                            Some(placement.pace),
+                            placement.age.map(NonZeroU8::get),
+                        )
+                    })
+                    .collect()
+            })
     }
 }
 
@@ -0,0 +1,437 @@
+// runs' own results format, which is what `runs convert` writes.  Once
+// a results page has been parsed, its finishers can be saved in this
+// format, so that a fragile MHTML snapshot can be archived as a small
+// file that reads back exactly the same, no matter what happens to the
+// timing company's pages (or our parsers for them).  It comes in two
+// forms.  JSON:
+//
+//     {
+//       "format": "runs-results",
+//       "version": 1,
+//       "race": { "name": "V Day Run", "date": "2025-02-15", "distance": "5K" },
+//       "did_not_finish": 3,
+//       "results": [
+//         { "name": "Jane Doe", "time": "0:19:52.4", "sex": "F", "event": "5K", "pace": "0:06:24", "age": 34 },
+//         ...
+//       ]
+//     }
+//
+// and CSV, where the race is in comment lines before the header:
+//
+//     # runs-results 1
+//     # name: V Day Run
+//     # date: 2025-02-15
+//     # did_not_finish: 3
+//     name,time,sex,event,pace,age
+//     Jane Doe,0:19:52.4,F,5K,0:06:24,34
+//
+// Everything in race is optional, as is did_not_finish, which is how
+// many started but didn't finish, for results that say.  So are sex
+// (M, F or X), event, pace (as the results listed it) and age.  Times
+// and paces are H:MM:SS with as many digits after the decimal point as
+// it takes to be exact.
+
+use {
+    crate::{RaceDetails, prelude::*},
+    anyhow::{Result, anyhow, bail},
+    digital_duration_nom::duration::Duration,
+    serde::{Deserialize, Serialize},
+    serde_json::{Map, Value},
+    std::io::Write,
+};
+
+const FORMAT: &str = "runs-results";
+const VERSION: u32 = 1;
+const DID_NOT_FINISH: &str = "did_not_finish";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Results {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    race: RaceDetails,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    did_not_finish: Option<usize>,
+    results: Vec<Placement>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct Placement {
+    name: String,
+    time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    event: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    age: Option<u8>,
+}
+
+impl Placement {
+    fn new(finisher: &crate::Finisher) -> Self {
+        Self {
+            name: finisher.name.clone(),
+            time: format_time(finisher.time),
+            sex: finisher.morf.map(|morf| morf.to_string()),
+            event: finisher.event.clone(),
+            pace: finisher.pace.map(format_time),
+            age: finisher.age,
+        }
+    }
+
+    pub(crate) fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        let placements = match json(document) {
+            Some(results) => results.results,
+            None => csv_placements(document.text())?,
+        };
+        Some(
+            placements
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, placement)| {
+                    let number = i + 1;
+                    let time = match parse_time(&placement.time) {
+                        Ok(time) => time,
+                        Err(e) => {
+                            warnings.push(Warning::BadField {
+                                field: "time".to_string(),
+                                error: e.to_string(),
+                                number,
+                                row: format!("{placement:?}"),
+                            });
+                            return None;
+                        }
+                    };
+                    let morf = placement.morf(number, warnings);
+                    // A pace that can't be read doesn't cost the
+                    // finisher their time.
+                    let pace = placement
+                        .pace
+                        .as_deref()
+                        .filter(|pace| !pace.is_empty())
+                        .and_then(|pace| match parse_time(pace) {
+                            Ok(pace) => Some(pace),
+                            Err(e) => {
+                                warnings.push(Warning::BadField {
+                                    field: "pace".to_string(),
+                                    error: e.to_string(),
+                                    number,
+                                    row: format!("{placement:?}"),
+                                });
+                                None
+                            }
+                        });
+                    Some((
+                        Cow::from(placement.name),
+                        time,
+                        morf,
+                        placement
+                            .event
+                            .filter(|event| !event.is_empty())
+                            .map(Cow::from),
+                        pace,
+                        placement.age,
+                    ))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Gender for Placement {
+    fn gender(&self) -> &str {
+        self.sex.as_deref().unwrap_or_default()
+    }
+}
+
+// The race's details, if the document is in this format.
+pub(crate) fn details(document: &Document) -> Option<RaceDetails> {
+    match json(document) {
+        Some(results) => Some(results.race),
+        None => csv_details(document.text()),
+    }
+}
+
+// How many didn't finish, if the document is in this format and says.
+pub(crate) fn did_not_finish(document: &Document) -> Option<usize> {
+    match json(document) {
+        Some(results) => results.did_not_finish,
+        None => split_csv(document.text())?
+            .0
+            .into_iter()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim() == DID_NOT_FINISH)?
+            .1
+            .trim()
+            .parse()
+            .ok(),
+    }
+}
+
+fn json(document: &Document) -> Option<Results> {
+    Results::deserialize(document.json()?)
+        .ok()
+        .filter(|results| results.format == FORMAT && results.version == VERSION)
+}
+
+// The CSV form's comment lines, without the "#", and the CSV after
+// them, if the text is in the CSV form.
+fn split_csv(text: &str) -> Option<(Vec<&str>, &str)> {
+    let mut rest = text.strip_prefix(&format!("# {FORMAT} {VERSION}"))?;
+    rest = rest.strip_prefix('\r').unwrap_or(rest).strip_prefix('\n')?;
+    let mut comments = vec![];
+    while let Some(line) = rest.strip_prefix('#') {
+        let (comment, after) = line.split_once('\n').unwrap_or((line, ""));
+        comments.push(comment.trim());
+        rest = after;
+    }
+    Some((comments, rest))
+}
+
+fn csv_details(text: &str) -> Option<RaceDetails> {
+    let fields = split_csv(text)?
+        .0
+        .into_iter()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(key, _)| key.trim() != DID_NOT_FINISH)
+        .map(|(key, value)| (key.trim().to_string(), Value::from(value.trim())))
+        .collect::<Map<_, _>>();
+    RaceDetails::deserialize(Value::Object(fields)).ok()
+}
+
+fn csv_placements(text: &str) -> Option<Vec<Placement>> {
+    ::csv::Reader::from_reader(split_csv(text)?.1.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .ok()
+}
+
+pub(crate) fn write_json(
+    writer: &mut dyn Write,
+    details: &RaceDetails,
+    did_not_finish: Option<usize>,
+    finishers: &[crate::Finisher],
+) -> Result<()> {
+    let results = Results {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        race: details.clone(),
+        did_not_finish,
+        results: placements(finishers),
+    };
+    serde_json::to_writer_pretty(&mut *writer, &results)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+pub(crate) fn write_csv(
+    writer: &mut dyn Write,
+    details: &RaceDetails,
+    did_not_finish: Option<usize>,
+    finishers: &[crate::Finisher],
+) -> Result<()> {
+    writeln!(writer, "# {FORMAT} {VERSION}")?;
+    if let Value::Object(fields) = serde_json::to_value(details)? {
+        for (key, value) in fields {
+            let value = match value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            if value.contains('\n') {
+                bail!("The race's {key} can't have more than one line");
+            }
+            writeln!(writer, "# {key}: {value}")?;
+        }
+    }
+    if let Some(did_not_finish) = did_not_finish {
+        writeln!(writer, "# {DID_NOT_FINISH}: {did_not_finish}")?;
+    }
+    let mut writer = ::csv::Writer::from_writer(writer);
+    // Written by hand, since serialize leaves out the header when
+    // there are no finishers.
+    writer.write_record(["name", "time", "sex", "event", "pace", "age"])?;
+    for placement in placements(finishers) {
+        writer.write_record([
+            placement.name,
+            placement.time,
+            placement.sex.unwrap_or_default(),
+            placement.event.unwrap_or_default(),
+            placement.pace.unwrap_or_default(),
+            placement.age.map(|age| age.to_string()).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn placements(finishers: &[crate::Finisher]) -> Vec<Placement> {
+    finishers.iter().map(Placement::new).collect()
+}
+
+// H:MM:SS, with only as many fractional digits as needed.
+fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    let hms = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    match time.subsec_nanos() {
+        0 => hms,
+        nanos => format!("{hms}.{}", format!("{nanos:09}").trim_end_matches('0')),
+    }
+}
+
+fn parse_time(time: &str) -> Result<Duration> {
+    let bad = || anyhow!("{time:?} isn't H:MM:SS");
+    let (hms, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut secs = 0;
+    let mut fields = 0;
+    for field in hms.split(':') {
+        fields += 1;
+        secs = secs * 60 + field.parse::<u64>().map_err(|_| bad())?;
+    }
+    if fields > 3
+        || fraction.len() > 9
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+        || (time.contains('.') && fraction.is_empty())
+    {
+        return Err(bad());
+    }
+    let nanos = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<9}").parse()?
+    };
+    Ok(Duration::new(secs, nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Finisher, MaleOrFemale::*};
+
+    fn finishers() -> Vec<Finisher> {
+        [
+            (
+                "Jane Doe",
+                Duration::new(1192, 400_000_000),
+                Some(Female),
+                Some("5K"),
+            ),
+            (
+                "John Roe",
+                Duration::new(3 * 3600 + 5, 123_456_789),
+                Some(Male),
+                None,
+            ),
+            ("Pat, \"Q\" Smith", Duration::new(59, 0), None, Some("10K")),
+            ("#1 Fan", Duration::new(3600, 0), Some(NonBinary), None),
+        ]
+        .map(|(name, time, morf, event)| Finisher {
+            name: name.to_string(),
+            time,
+            morf,
+            event: event.map(str::to_string),
+            pace: (name == "Jane Doe").then(|| Duration::new(384, 0)),
+            age: (name == "John Roe").then_some(62),
+        })
+        .to_vec()
+    }
+
+    // Writes the details, the count of who didn't finish and the
+    // finishers to the bytes and returns them.
+    type Writer = fn(Vec<u8>, &RaceDetails, Option<usize>, &[Finisher]) -> Vec<u8>;
+
+    fn round_trip(write: Writer) {
+        let details = RaceDetails {
+            name: Some("V Day Run".to_string()),
+            date: Some("2025-02-15".to_string()),
+            distance: Some("5".to_string()),
+            units: Some(crate::Units::Km),
+            surface: Some(crate::Surface::Road),
+            ..RaceDetails::default()
+        };
+        let text = String::from_utf8(write(vec![], &details, Some(3), &finishers())).unwrap();
+        let document = Document::new(&text);
+        let mut warnings = vec![];
+        let results = Placement::names_and_times(&document, &mut warnings).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(results.len(), 4);
+        for ((name, time, morf, event, pace, age), finisher) in results.into_iter().zip(finishers())
+        {
+            assert_eq!(name, finisher.name);
+            assert_eq!(time, finisher.time);
+            assert_eq!(morf, finisher.morf);
+            assert_eq!(event.as_deref(), finisher.event.as_deref());
+            assert_eq!(pace, finisher.pace);
+            assert_eq!(age, finisher.age);
+        }
+        assert_eq!(super::details(&document).unwrap(), details);
+        assert_eq!(did_not_finish(&document), Some(3));
+
+        let text = String::from_utf8(write(vec![], &details, None, &finishers())).unwrap();
+        assert_eq!(did_not_finish(&Document::new(&text)), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(|mut bytes, details, did_not_finish, finishers| {
+            write_json(&mut bytes, details, did_not_finish, finishers).unwrap();
+            bytes
+        });
+        round_trip(|mut bytes, details, did_not_finish, finishers| {
+            write_csv(&mut bytes, details, did_not_finish, finishers).unwrap();
+            bytes
+        });
+    }
+
+    #[test]
+    fn test_without_pace() {
+        let document = Document::new("# runs-results 1\nname,time\nJane Doe,0:19:52.4\n");
+        let results = Placement::names_and_times(&document, &mut vec![]).unwrap();
+        assert_eq!(
+            results,
+            [(
+                Cow::from("Jane Doe"),
+                Duration::new(1192, 400_000_000),
+                None,
+                None,
+                None,
+                None
+            )]
+        );
+    }
+
+    #[test]
+    fn test_not_canonical() {
+        for text in [
+            "name,time,sex,event\nJane Doe,0:19:52.4,F,5K\n",
+            r#"{"format": "runs-results", "version": 2, "results": []}"#,
+            r#"{"results": []}"#,
+        ] {
+            let document = Document::new(text);
+            assert!(Placement::names_and_times(&document, &mut vec![]).is_none());
+            assert!(details(&document).is_none());
+        }
+    }
+
+    #[test]
+    fn test_times() {
+        for (text, time) in [
+            ("0:19:52.4", Duration::new(1192, 400_000_000)),
+            ("3:00:05.123456789", Duration::new(10805, 123_456_789)),
+            ("0:00:59", Duration::new(59, 0)),
+        ] {
+            assert_eq!(format_time(time), text);
+            assert_eq!(parse_time(text).unwrap(), time);
+        }
+        assert_eq!(
+            parse_time("19:52.4").unwrap(),
+            Duration::new(1192, 400_000_000)
+        );
+        for bad in ["", "1:2:3:4", "0:19:52.", "0:19:52.4s", "DNF"] {
+            assert!(parse_time(bad).is_err());
+        }
+    }
+}
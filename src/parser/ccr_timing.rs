@@ -43,12 +43,24 @@ impl Placement<'_> {
         }
     }
 
-    pub fn soloist_names_and_times(input: &str) -> OptionalResults<'_> {
-        Results::new(input).map(|results| {
+    pub fn soloist_names_and_times<'a>(
+        document: &Document<'a>,
+        _warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Results::new(document.text()).map(|results| {
             results
                 .soloists
                 .iter()
-                .map(|soloist| (Cow::from(soloist.name), soloist.total, None))
+                .map(|soloist| {
+                    (
+                        Cow::from(soloist.name),
+                        soloist.total,
+                        None,
+                        (!soloist.category.is_empty()).then_some(Cow::from(soloist.category)),
+                        None,
+                        None,
+                    )
+                })
                 .collect()
         })
     }
@@ -338,10 +350,9 @@ fn placement(
             category_column,
         )| {
             let total = total.unwrap();
-            let category = match header_category {
-                Some(value) => value,
-                _ => category_column.expect("no category anywhere"),
-            };
+            // category_column is only parsed when there's no
+            // header_category, so one of them is always present.
+            let category = header_category.or(category_column).unwrap_or_default();
             Placement {
                 category,
                 category_place,
@@ -15,7 +15,7 @@ pub struct Placement {
     first_name: String,
     gender: String,
     age: NonZeroU8,
-    _event: String, // "6" for 6 miler
+    event: String, // "6" for 6 miler
     bib: NonZeroU8,
     time: Duration,
     // _division is a String, but it could be an Option<> of an enum whose
@@ -35,16 +35,23 @@ impl Placement {
             .ok()
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.text()).map(|results| {
             results
                 .into_iter()
-                .map(|placement| {
-                    let morf = placement.morf();
+                .enumerate()
+                .map(|(i, placement)| {
+                    let morf = placement.morf(i + 1, warnings);
                     (
                         Cow::from(format!("{} {}", placement.first_name, placement.last_name)),
                         placement.time,
                         morf,
+                        Some(Cow::from(placement.event)),
+                        None,
+                        Some(placement.age.get()),
                     )
                 })
                 .collect()
@@ -2,7 +2,7 @@
 
 use {
     crate::prelude::*, digital_duration_nom::duration::Duration, serde::Deserialize,
-    std::num::NonZeroU16,
+    serde_json::Value, std::num::NonZeroU16,
 };
 
 #[allow(dead_code)]
@@ -16,22 +16,36 @@ pub struct Placement {
 }
 
 impl Placement {
-    fn results(contents: &str) -> Option<Vec<Self>> {
-        contents
-            .trim()
-            .split('\n')
-            .map(serde_json::from_str::<Vec<_>>)
+    fn results(lines: &[Value]) -> Option<Vec<Self>> {
+        lines
+            .iter()
+            .map(Vec::<Self>::deserialize)
             .collect::<Result<Vec<_>, _>>()
             .ok()
             .map(|v| v.into_iter().flatten().collect())
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
-            results
-                .into_iter()
-                .map(|placement| (Cow::from(placement.name), placement.time, None))
-                .collect()
-        })
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        _warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        document
+            .json_lines()
+            .and_then(Self::results)
+            .map(|results| {
+                results
+                    .into_iter()
+                    .map(|placement| {
+                        (
+                            Cow::from(placement.name),
+                            placement.time,
+                            None,
+                            None,
+                            Some(placement.pace),
+                            None,
+                        )
+                    })
+                    .collect()
+            })
     }
 }
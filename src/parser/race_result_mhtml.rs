@@ -103,33 +103,54 @@ impl<'doc> Placement<'doc> {
             .and_then(|table| placements(table, &fields_for_indexes(table)))
     }
 
-    fn morf(&self, male: &str, female: &str) -> Option<MaleOrFemale> {
+    fn morf(
+        &self,
+        number: usize,
+        male: &str,
+        female: &str,
+        warnings: &mut Vec<Warning>,
+    ) -> Option<MaleOrFemale> {
         use MaleOrFemale::*;
 
         match self.gender_count() {
-            None => panic!("No gender splitter"),
+            None => {
+                warnings.push(Warning::NoGenderCount {
+                    number,
+                    row: format!("{self:?}"),
+                });
+                None
+            }
             Some(text) if text == male => Some(Male),
             Some(text) if text == female => Some(Female),
             Some(_text) => {
-                eprintln!("Assuming Non-Binary: {self:?}");
+                warnings.push(Warning::AssumedNonBinary {
+                    number,
+                    row: format!("{self:?}"),
+                });
                 Some(NonBinary)
             }
         }
     }
 }
 
-pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-    let document = Html::parse_document(input);
-    Placement::results(&document).and_then(|placements| {
+pub fn names_and_times<'a>(
+    document: &Document<'a>,
+    warnings: &mut Vec<Warning>,
+) -> OptionalResults<'a> {
+    Placement::results(document.html()).and_then(|placements| {
         let (male, female) = male_and_female_counts(&placements)?;
         Some(
             placements
                 .into_iter()
-                .map(|p| {
+                .enumerate()
+                .map(|(i, p)| {
                     (
                         p.name.to_string().into(),
                         p.final_time,
-                        p.morf(male, female),
+                        p.morf(i + 1, male, female, warnings),
+                        None,
+                        Some(p.pace),
+                        None,
                     )
                 })
                 .collect(),
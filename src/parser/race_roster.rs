@@ -33,12 +33,24 @@ const CHIP_TIME: &str = "Chip Time";
 const PLACE: &str = "Overall Place";
 
 impl Placement<'_> {
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        let document = Html::parse_document(input);
-        Self::results(&document).map(|results| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.html()).map(|results| {
             results
                 .into_iter()
-                .map(|p| (p.name.to_string().into(), p.chip_time, p.morf()))
+                .enumerate()
+                .map(|(i, p)| {
+                    (
+                        p.name.to_string().into(),
+                        p.chip_time,
+                        p.morf(i + 1, warnings),
+                        None,
+                        None,
+                        Some(p.age.get()),
+                    )
+                })
                 .collect()
         })
     }
@@ -52,8 +52,11 @@ impl Placement<'_> {
         }
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        _warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.text()).map(|results| {
             let mut names_and_times: Vec<_> = results
                 .into_iter()
                 .map(|placement| {
@@ -61,6 +64,9 @@ impl Placement<'_> {
                         Cow::from(placement.name),
                         placement.chip_time,
                         placement.gender,
+                        None,
+                        None,
+                        placement.age.map(NonZeroU8::get),
                     )
                 })
                 .collect();
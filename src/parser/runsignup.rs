@@ -2,6 +2,7 @@ use {
     crate::prelude::*,
     digital_duration_nom::duration::Duration,
     serde::{Deserialize, Deserializer},
+    serde_json::Value,
 };
 
 #[allow(dead_code)]
@@ -18,28 +19,43 @@ pub struct Placement {
 
 // TODO: merge with chrono_track and athlinks
 impl Placement {
-    pub fn results(contents: &str) -> Option<Vec<Self>> {
-        contents
-            .trim()
-            .split('\n')
-            .map(serde_json::from_str::<Vec<_>>)
+    pub fn results(lines: &[Value]) -> Option<Vec<Self>> {
+        lines
+            .iter()
+            .map(Vec::<Self>::deserialize)
             .collect::<Result<Vec<_>, _>>()
             .ok()
             .map(|v| v.into_iter().flatten().collect())
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
-            results
-                .into_iter()
-                .map(|placement| (Cow::from(placement.name), placement.clock_time, None))
-                .collect()
-        })
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        _warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        document
+            .json_lines()
+            .and_then(Self::results)
+            .map(|results| {
+                results
+                    .into_iter()
+                    .map(|placement| {
+                        (
+                            Cow::from(placement.name),
+                            placement.clock_time,
+                            None,
+                            None,
+                            None,
+                            None,
+                        )
+                    })
+                    .collect()
+            })
     }
 }
 
 // TODO: this should be put somewhere else
 fn duration_deserializer<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
     let s: String = String::deserialize(d)?;
-    Ok(s.parse::<Duration>().unwrap()) // TODO: map_err instead Ok(...unwrap())
+    s.parse::<Duration>()
+        .map_err(|e| serde::de::Error::custom(format!("{e:?}")))
 }
@@ -113,9 +113,11 @@ impl<'doc> Placement<'doc> {
             .next()
             .and_then(|table| Self::placements(table, &fields_for_indexes(table)))
     }
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        let document = Html::parse_document(input);
-        Placement::results(&document).map(|placements| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        _warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Placement::results(document.html()).map(|placements| {
             placements
                 .into_iter()
                 .map(|p| {
@@ -123,6 +125,9 @@ impl<'doc> Placement<'doc> {
                         format!("{} {}", p.name[0], p.name[1]).into(),
                         p.chip_time,
                         Some(p.gender),
+                        None,
+                        Some(p.pace),
+                        Some(p.age.get()),
                     )
                 })
                 .collect()
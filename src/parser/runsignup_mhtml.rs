@@ -32,13 +32,17 @@ impl Placement<'_> {
         results(contents).ok().map(|(_, results)| results)
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.text()).map(|results| {
             results
                 .into_iter()
-                .map(|placement| {
-                    let morf = placement.morf();
-                    (placement.name, placement.time, morf)
+                .enumerate()
+                .map(|(i, placement)| {
+                    let morf = placement.morf(i + 1, warnings);
+                    (placement.name, placement.time, morf, None, None, None)
                 })
                 .collect()
         })
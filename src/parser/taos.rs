@@ -65,15 +65,22 @@ impl<'a> Placement<'a> {
         }
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
+    pub fn names_and_times<'i>(
+        document: &Document<'i>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'i> {
+        Self::results(document.text()).map(|results| {
             let mut names_and_times: Vec<_> = results
                 .into_iter()
-                .map(|placement| {
+                .enumerate()
+                .map(|(i, placement)| {
                     (
                         Cow::from(placement.name()),
                         placement.time,
-                        placement.morf(),
+                        placement.morf(i + 1, warnings),
+                        None,
+                        None,
+                        Some(placement.age.get()),
                     )
                 })
                 .collect();
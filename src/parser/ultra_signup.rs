@@ -1,6 +1,6 @@
 use {
     crate::prelude::*, digital_duration_nom::duration::Duration, serde::Deserialize,
-    std::num::NonZeroU8,
+    serde_json::Value, std::num::NonZeroU8,
 };
 
 #[allow(dead_code)]
@@ -44,12 +44,15 @@ struct StatusWithCount {
 pub struct StatusesWithPlacements(Vec<(StatusWithCount, Vec<Placement>)>);
 
 impl StatusesWithPlacements {
-    fn results(contents: &str) -> Option<Self> {
-        serde_json::from_str(contents).ok()
+    fn results(json: &Value) -> Option<Self> {
+        Self::deserialize(json).ok()
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).and_then(|swp| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        document.json().and_then(Self::results).and_then(|swp| {
             swp.0
                 .into_iter()
                 .find(|(swc, _)| {
@@ -64,15 +67,54 @@ impl StatusesWithPlacements {
                 .map(|(_, placements)| {
                     placements
                         .into_iter()
-                        .map(|p| {
+                        .enumerate()
+                        .map(|(i, p)| {
                             (
                                 Cow::from(format!("{} {}", p.first, p.last)),
                                 p.time,
-                                p.morf(),
+                                p.morf(i + 1, warnings),
+                                None,
+                                None,
+                                Some(p.age.get()),
                             )
                         })
                         .collect::<Vec<_>>()
                 })
         })
     }
+
+    // Everyone who started but didn't finish, including those who were
+    // disqualified.
+    pub fn did_not_finish(document: &Document) -> Option<usize> {
+        document.json().and_then(Self::results).map(|swp| {
+            swp.0
+                .iter()
+                .filter(|(swc, _)| {
+                    matches!(swc.status, Status::DidNotFinish | Status::Disqualified)
+                })
+                .map(|(swc, _)| usize::from(swc.count))
+                .sum()
+        })
+    }
+
+    pub fn non_finishers(document: &Document) -> Option<Vec<NonFinisher>> {
+        document.json().and_then(Self::results).map(|swp| {
+            swp.0
+                .into_iter()
+                .filter_map(|(swc, placements)| {
+                    let status = match swc.status {
+                        Status::DidNotFinish => crate::Status::DidNotFinish,
+                        Status::DidNotStart => crate::Status::DidNotStart,
+                        Status::Disqualified => crate::Status::Disqualified,
+                        Status::Finishers | Status::UnofficialFinish => return None,
+                    };
+                    Some(placements.into_iter().map(move |p| NonFinisher {
+                        name: format!("{} {}", p.first, p.last),
+                        status,
+                    }))
+                })
+                .flatten()
+                .collect()
+        })
+    }
 }
@@ -23,6 +23,8 @@ pub(crate) struct Placement {
     gp: u16,
     time: Duration,
     rank: f32,
+    // which results row this is, from 1 (see warning.rs)
+    number: usize,
 }
 
 impl Gender for Placement {
@@ -48,37 +50,57 @@ pub(crate) enum Field {
 fn get_and_parse<T: FromStr>(
     values: &mut HashMap<Field, String>,
     field: Field,
-    label: &str,
+    label: &'static str,
+    number: usize,
+    warnings: &mut Vec<Warning>,
 ) -> Option<T>
 where
     <T as FromStr>::Err: Debug,
 {
-    values
-        .get(&field)
-        .or_else(|| {
-            eprintln!("no {label} in {values:?}");
-            None
-        })
-        .and_then(|p| {
-            p.parse()
-                .inspect_err(|e| {
-                    if label != "age" {
-                        eprintln!("Can't convert {label}: {e:?}, values: {values:?}");
-                    }
-                })
-                .ok()
+    let Some(value) = values.get(&field) else {
+        warnings.push(Warning::MissingField {
+            field: label.to_string(),
+            number,
+            row: format!("{values:?}"),
+        });
+        return None;
+    };
+    value
+        .parse()
+        .inspect_err(|e| {
+            warnings.push(Warning::BadField {
+                field: label.to_string(),
+                error: format!("{e:?}"),
+                number,
+                row: format!("{values:?}"),
+            })
         })
+        .ok()
 }
 
-fn remove(values: &mut HashMap<Field, String>, field: Field, label: &str) -> Option<String> {
+fn remove(
+    values: &mut HashMap<Field, String>,
+    field: Field,
+    label: &'static str,
+    number: usize,
+    warnings: &mut Vec<Warning>,
+) -> Option<String> {
     values.remove(&field).or_else(|| {
-        eprintln!("no {label} in {values:?}");
+        warnings.push(Warning::MissingField {
+            field: label.to_string(),
+            number,
+            row: format!("{values:?}"),
+        });
         None
     })
 }
 
 impl Placement {
-    fn from_list_result<'a>(tds: impl Iterator<Item = ElementRef<'a>>) -> Option<Self> {
+    fn from_list_result<'a>(
+        tds: impl Iterator<Item = ElementRef<'a>>,
+        number: usize,
+        warnings: &mut Vec<Warning>,
+    ) -> Option<Self> {
         use Field::*;
 
         let mut values = HashMap::new();
@@ -93,10 +115,11 @@ impl Placement {
             }
         }
 
-        let place = get_and_parse(&mut values, Place, "place")?;
-        let age = get_and_parse(&mut values, Age, "age");
-        let gp = get_and_parse(&mut values, Gp, "gender place")?;
-        let rank = get_and_parse(&mut values, Rank, "rank")?;
+        let place = get_and_parse(&mut values, Place, "place", number, warnings)?;
+        // age is optional, so there's no need to warn about it
+        let age = values.get(&Age).and_then(|age| age.parse().ok());
+        let gp = get_and_parse(&mut values, Gp, "gender place", number, warnings)?;
+        let rank = get_and_parse(&mut values, Rank, "rank", number, warnings)?;
 
         // DNF and DNS may have blank times.  Elsewhere, they have 0
         // times.  In theory, DNF and DNS have 0 for both place and
@@ -108,21 +131,20 @@ impl Placement {
         let time = if place == 0 && gp == 0 {
             Duration::new(9_999_999, 0)
         } else {
-            get_and_parse(&mut values, Time, "time")?
+            get_and_parse(&mut values, Time, "time", number, warnings)?
         };
 
         // We remove from least specific to most specific, since our
-        // error messages dump values.  The error messages are just
-        // there to help if we're parsing a file and getting
-        // surprising results, so the fact that we're removing some
-        // values before printing the error message is not that big of
-        // a deal.
+        // warnings dump values.  The warnings are just there to help
+        // if we're parsing a file and getting surprising results, so
+        // the fact that we're removing some values before recording
+        // the warning is not that big of a deal.
 
         let state = values.remove(&State);
         let city = values.remove(&City);
-        let gender = remove(&mut values, Gender, "gender")?;
-        let first = remove(&mut values, First, "first name")?;
-        let last = remove(&mut values, Last, "last name")?;
+        let gender = remove(&mut values, Gender, "gender", number, warnings)?;
+        let first = remove(&mut values, First, "first name", number, warnings)?;
+        let last = remove(&mut values, Last, "last name", number, warnings)?;
 
         Some(Self {
             place,
@@ -135,6 +157,7 @@ impl Placement {
             gp,
             time,
             rank,
+            number,
         })
     }
 }
@@ -150,14 +173,14 @@ struct StatusWithCount {
 pub struct StatusesWithPlacements(Vec<(StatusWithCount, Vec<Placement>)>);
 
 impl StatusesWithPlacements {
-    fn results(contents: &str) -> Option<Self> {
+    fn results(document: &Html, warnings: &mut Vec<Warning>) -> Option<Self> {
         let mut results = None;
         let mut placements = None;
+        let mut number = 0;
 
         let tbody = Selector::parse("tbody").unwrap();
         let tr = Selector::parse("tr").unwrap();
         let td = Selector::parse("td").unwrap();
-        let document = Html::parse_document(contents);
 
         for body in document.select(&tbody) {
             for tr in body.select(&tr) {
@@ -191,9 +214,12 @@ impl StatusesWithPlacements {
                     let mut tds = tr.select(&td);
                     if let Some(td) = tds.next()
                         && td.value().attr("aria-describedby") == Some("list_results")
-                        && let Some(placement) = Placement::from_list_result(tds)
                     {
-                        placements.push(placement);
+                        number += 1;
+                        if let Some(placement) = Placement::from_list_result(tds, number, warnings)
+                        {
+                            placements.push(placement);
+                        }
                     }
                 }
             }
@@ -207,8 +233,11 @@ impl StatusesWithPlacements {
         results.map(Self)
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).and_then(|swp| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.html(), warnings).and_then(|swp| {
             swp.0
                 .into_iter()
                 .find(|(StatusWithCount { status, .. }, _)| status == "Finishers")
@@ -219,11 +248,49 @@ impl StatusesWithPlacements {
                             (
                                 Cow::from(format!("{} {}", p.first, p.last)),
                                 p.time,
-                                p.morf(),
+                                p.morf(p.number, warnings),
+                                None,
+                                None,
+                                p.age.map(NonZeroU8::get),
                             )
                         })
                         .collect()
                 })
         })
     }
+
+    // Everyone who started but didn't finish, including those who were
+    // disqualified.
+    pub fn did_not_finish(document: &Document) -> Option<usize> {
+        Self::results(document.html(), &mut vec![]).map(|swp| {
+            swp.0
+                .iter()
+                .filter(|(StatusWithCount { status, .. }, _)| {
+                    status == "Did Not Finish" || status == "Disqualified"
+                })
+                .map(|(StatusWithCount { count, .. }, _)| usize::from(*count))
+                .sum()
+        })
+    }
+
+    pub fn non_finishers(document: &Document) -> Option<Vec<NonFinisher>> {
+        Self::results(document.html(), &mut vec![]).map(|swp| {
+            swp.0
+                .into_iter()
+                .filter_map(|(StatusWithCount { status, .. }, placements)| {
+                    let status = match status.as_str() {
+                        "Did Not Finish" => crate::Status::DidNotFinish,
+                        "Did Not Start" => crate::Status::DidNotStart,
+                        "Disqualified" => crate::Status::Disqualified,
+                        _ => return None,
+                    };
+                    Some(placements.into_iter().map(move |p| NonFinisher {
+                        name: format!("{} {}", p.first, p.last),
+                        status,
+                    }))
+                })
+                .flatten()
+                .collect()
+        })
+    }
 }
@@ -25,6 +25,8 @@ pub struct Placement<'a> {
     pub age: Option<u8>,
     pub gender: Option<Cow<'a, str>>,
     pub finish_time: Duration,
+    // e.g., "Female - Overall", when the page has more than one table
+    pub table_title: Option<&'a str>,
 }
 
 impl Placement<'_> {
@@ -44,13 +46,24 @@ impl Placement<'_> {
         }
     }
 
-    pub fn names_and_times(input: &str) -> OptionalResults<'_> {
-        Self::results(input).map(|results| {
+    pub fn names_and_times<'a>(
+        document: &Document<'a>,
+        warnings: &mut Vec<Warning>,
+    ) -> OptionalResults<'a> {
+        Self::results(document.text()).map(|results| {
             results
                 .into_iter()
-                .map(|placement| {
-                    let morf = placement.morf();
-                    (placement.name, placement.finish_time, morf)
+                .enumerate()
+                .map(|(i, placement)| {
+                    let morf = placement.morf(i + 1, warnings);
+                    (
+                        placement.name,
+                        placement.finish_time,
+                        morf,
+                        placement.table_title.map(Cow::from),
+                        None,
+                        placement.age,
+                    )
                 })
                 .collect()
         })
@@ -80,15 +93,34 @@ impl Gender for Placement<'_> {
 
 fn results(input: &str) -> IResult<&str, Vec<Placement<'_>>> {
     map(
-        many1(preceded(
-            take_until_and_consume("<tbody>"),
+        many1((
+            map(take_until_and_consume("<tbody>"), table_title),
             many1(placement),
         )),
-        |v| v.into_iter().flatten().collect::<Vec<_>>(),
+        |v| {
+            v.into_iter()
+                .flat_map(|(table_title, placements)| {
+                    placements.into_iter().map(move |placement| Placement {
+                        table_title,
+                        ..placement
+                    })
+                })
+                .collect::<Vec<_>>()
+        },
     )
     .parse(input)
 }
 
+// The title of the table that the <tbody> belongs to, which is the last
+// one before it, e.g.,
+// <span id="CPH1_repRaceDetails_lbTableTitle_1"> Male - Overall</span>
+fn table_title(before_tbody: &str) -> Option<&str> {
+    let (_, title) = before_tbody.rsplit_once("_lbTableTitle_")?;
+    let (_, title) = title.split_once('>')?;
+    let (title, _) = title.split_once("</span>")?;
+    Some(title.trim()).filter(|title| !title.is_empty())
+}
+
 fn placement(input: &str) -> IResult<&str, Placement<'_>> {
     map(
         (
@@ -101,7 +133,6 @@ fn placement(input: &str) -> IResult<&str, Placement<'_>> {
             terminated(finish_time, take_until_and_consume("</tr>")),
         ),
         |(place, bib, name_and_team, category, age, gender, finish_time)| {
-            let finish_time = Duration::from_str(finish_time).unwrap();
             let (name, team) = name_and_team;
             Placement {
                 place,
@@ -112,6 +143,7 @@ fn placement(input: &str) -> IResult<&str, Placement<'_>> {
                 age,
                 gender,
                 finish_time,
+                table_title: None,
             }
         },
     )
@@ -222,8 +254,8 @@ fn gender(input: &str) -> IResult<&str, Option<Cow<'_, str>>> {
     optional_inside_td("r-gender").parse(input)
 }
 
-fn finish_time(input: &str) -> IResult<&str, &str> {
-    inside_td("r-finish-time").parse(input)
+fn finish_time(input: &str) -> IResult<&str, Duration> {
+    map_res(inside_td("r-finish-time"), Duration::from_str).parse(input)
 }
 
 #[cfg(test)]
@@ -244,4 +276,11 @@ mod tests {
         let placement = placement(lines).unwrap().1;
         println!("placement = {:?}", placement);
     }
+
+    #[test]
+    fn test_table_title() {
+        let before = "<span id=\"CPH1_repRaceDetails_lbTableTitle_0\"> Female - Overall</span>\r\n</li></ul>\r\n<table><thead></thead>\r\n    ";
+        assert_eq!(Some("Female - Overall"), table_title(before));
+        assert_eq!(None, table_title("<table><thead></thead>\r\n    "));
+    }
 }
@@ -0,0 +1,445 @@
+// Set algebra over the finishers of named groups of files, e.g.,
+//
+//   runs query 'cw & ccw' 'cw=assets/hrh/*[02468].mhtml' 'ccw=assets/hrh/*[13579].mhtml'
+//
+// finds everyone who has finished Hardrock in both directions.
+//
+// Expressions are made of group names, parentheses and these operators,
+// from loosest to tightest binding:
+//
+//   a | b   finished something in a or something in b (union)
+//   a - b   finished something in a but nothing in b (difference)
+//   a & b   finished something in both a and b (intersection)
+//
+// as well as atleast(K, e1, e2, ...), which is everyone who is in at
+// least K of its operands.  An operand that is just a group name counts
+// once for each file in that group the person finished, so
+// atleast(5, cw) is everyone with at least five clockwise finishes.
+//
+// The patterns in the group definitions are expanded by runs itself,
+// since a shell won't expand a word that starts with "cw=".
+
+use {
+    crate::{
+        Finisher, Loader, RaceResults, load_in_order, names,
+        output::{Output, Times, seconds, write_json, write_table},
+    },
+    anyhow::{Context, Result, anyhow, bail},
+    digital_duration_nom::duration::Duration,
+    glob::{MatchOptions, Pattern},
+    nom::{
+        IResult, Parser,
+        branch::alt,
+        bytes::complete::{tag, take_while1},
+        character::complete::{digit1, multispace0},
+        combinator::{all_consuming, map, map_res},
+        multi::{fold_many0, many1},
+        sequence::{delimited, preceded, terminated},
+    },
+    serde::Serialize,
+    std::{
+        collections::{BTreeSet, HashMap},
+        fs,
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub(crate) struct Group {
+    name: String,
+    pattern: String,
+}
+
+impl FromStr for Group {
+    type Err = String;
+
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg.split_once('=') {
+            Some((name, pattern)) if is_group_name(name) && !pattern.is_empty() => Ok(Group {
+                name: name.to_string(),
+                pattern: pattern.to_string(),
+            }),
+            _ => Err(format!("expected NAME=PATTERN, got {arg}")),
+        }
+    }
+}
+
+fn is_group_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_group_name_char)
+}
+
+fn is_group_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+struct FileResults {
+    path: PathBuf,
+    times: HashMap<String, Duration>,
+}
+
+impl FileResults {
+    fn new(race: RaceResults, loader: &Loader) -> Result<Self> {
+        loader.check(&race)?;
+        if race.finishers.is_empty() {
+            eprintln!("No finishers found in {}", race.path.display());
+        }
+        let mut times = HashMap::new();
+        for Finisher { name, time, .. } in race.finishers {
+            times
+                .entry(names::canonical(name.into()).into_owned())
+                .and_modify(|old: &mut Duration| *old = (*old).min(time))
+                .or_insert(time);
+        }
+        Ok(Self {
+            path: race.path,
+            times,
+        })
+    }
+}
+
+pub(crate) fn summarize(
+    expression: &str,
+    groups: &[Group],
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let expression = Expression::from_str(expression)?;
+
+    // Groups are kept in the order they're first mentioned, so that the
+    // supporting times come out in that order, too.
+    let mut paths = vec![];
+    for Group { name, pattern } in groups {
+        paths.extend(
+            expand(pattern)?
+                .into_iter()
+                .map(|path| (name.as_str(), path)),
+        );
+    }
+    let races = load_in_order(&paths, |(_, path)| loader.results(path))?;
+    let mut files: Vec<(&str, Vec<FileResults>)> = vec![];
+    for ((name, _), race) in paths.iter().zip(races) {
+        let results = FileResults::new(race, loader)?;
+        match files.iter_mut().find(|(n, _)| n == name) {
+            Some((_, old)) => old.push(results),
+            None => files.push((name, vec![results])),
+        }
+    }
+    let lookup = files
+        .iter()
+        .map(|(name, results)| (*name, results.as_slice()))
+        .collect::<HashMap<_, _>>();
+
+    let rows = expression
+        .evaluate(&lookup)?
+        .into_iter()
+        .map(|name| {
+            let times = files
+                .iter()
+                .flat_map(|(group, results)| {
+                    results.iter().filter_map(|FileResults { path, times }| {
+                        times.get(&name).map(|&time| SupportingTime {
+                            group,
+                            race: race(path),
+                            time,
+                        })
+                    })
+                })
+                .collect();
+            Row { name, times }
+        })
+        .collect::<Vec<_>>();
+
+    match output {
+        Output::Text => print(&rows),
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => {
+            // One column per file, in the same order as in the text output.
+            let columns = files
+                .iter()
+                .flat_map(|(group, results)| {
+                    results
+                        .iter()
+                        .map(move |results| (*group, race(&results.path)))
+                })
+                .collect::<Vec<_>>();
+            let mut header = vec!["name".to_string()];
+            header.extend(
+                columns
+                    .iter()
+                    .map(|(group, race)| format!("{group} {race}")),
+            );
+            write_table(
+                output,
+                header,
+                rows.iter().map(|row| {
+                    let mut record = vec![row.name.clone()];
+                    record.extend(columns.iter().map(|(group, race)| {
+                        row.times
+                            .iter()
+                            .find(|t| t.group == *group && t.race == *race)
+                            .map(|t| times.format(t.time))
+                            .unwrap_or_default()
+                    }));
+                    record
+                }),
+            )?
+        }
+    }
+    Ok(())
+}
+
+fn race(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+#[derive(Serialize)]
+struct Row<'a> {
+    name: String,
+    times: Vec<SupportingTime<'a>>,
+}
+
+#[derive(Serialize)]
+struct SupportingTime<'a> {
+    group: &'a str,
+    race: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+}
+
+fn print(rows: &[Row]) {
+    let group_width = rows
+        .iter()
+        .flat_map(|row| row.times.iter().map(|time| time.group.len()))
+        .max()
+        .unwrap_or(0);
+    let mut need_nl = false;
+    for Row { name, times } in rows {
+        if need_nl {
+            println!();
+        } else {
+            need_nl = true;
+        }
+        println!("{name}");
+        for SupportingTime { group, race, time } in times {
+            println!("    {group:group_width$} {time:>10.1} {race}");
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Group(String),
+    Union(Box<Expression>, Box<Expression>),
+    Difference(Box<Expression>, Box<Expression>),
+    Intersection(Box<Expression>, Box<Expression>),
+    AtLeast(usize, Vec<Expression>),
+}
+
+impl FromStr for Expression {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        all_consuming(terminated(expression, multispace0))
+            .parse(input)
+            .map(|(_, expression)| expression)
+            .map_err(|e| anyhow!("Could not parse expression {input:?}: {e}"))
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, files: &HashMap<&str, &[FileResults]>) -> Result<BTreeSet<String>> {
+        use Expression::*;
+
+        Ok(match self {
+            Group(name) => group(files, name)?
+                .iter()
+                .flat_map(|results| results.times.keys().cloned())
+                .collect(),
+            Union(a, b) => &a.evaluate(files)? | &b.evaluate(files)?,
+            Difference(a, b) => &a.evaluate(files)? - &b.evaluate(files)?,
+            Intersection(a, b) => &a.evaluate(files)? & &b.evaluate(files)?,
+            AtLeast(k, operands) => {
+                let mut counts = HashMap::<String, usize>::new();
+                for operand in operands {
+                    if let Group(name) = operand {
+                        for results in group(files, name)? {
+                            for name in results.times.keys() {
+                                *counts.entry(name.clone()).or_default() += 1;
+                            }
+                        }
+                    } else {
+                        for name in operand.evaluate(files)? {
+                            *counts.entry(name).or_default() += 1;
+                        }
+                    }
+                }
+                counts
+                    .into_iter()
+                    .filter_map(|(name, count)| (count >= *k).then_some(name))
+                    .collect()
+            }
+        })
+    }
+}
+
+fn group<'a>(files: &HashMap<&str, &'a [FileResults]>, name: &str) -> Result<&'a [FileResults]> {
+    files
+        .get(name)
+        .copied()
+        .ok_or_else(|| anyhow!("No group named {name}"))
+}
+
+fn expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = term(input)?;
+    fold_many0(
+        (token(alt((tag("|"), tag("-")))), term),
+        move || first.clone(),
+        |a, (op, b)| match op {
+            "|" => Expression::Union(Box::new(a), Box::new(b)),
+            _ => Expression::Difference(Box::new(a), Box::new(b)),
+        },
+    )
+    .parse(input)
+}
+
+fn term(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = factor(input)?;
+    fold_many0(
+        preceded(token(tag("&")), factor),
+        move || first.clone(),
+        |a, b| Expression::Intersection(Box::new(a), Box::new(b)),
+    )
+    .parse(input)
+}
+
+fn factor(input: &str) -> IResult<&str, Expression> {
+    alt((
+        at_least,
+        delimited(token(tag("(")), expression, token(tag(")"))),
+        map(token(take_while1(is_group_name_char)), |name: &str| {
+            Expression::Group(name.to_string())
+        }),
+    ))
+    .parse(input)
+}
+
+fn at_least(input: &str) -> IResult<&str, Expression> {
+    map(
+        delimited(
+            (token(tag("atleast")), token(tag("("))),
+            (
+                map_res(token(digit1), |digits: &str| digits.parse()),
+                many1(preceded(token(tag(",")), expression)),
+            ),
+            token(tag(")")),
+        ),
+        |(k, operands)| Expression::AtLeast(k, operands),
+    )
+    .parse(input)
+}
+
+fn token<'a, O>(
+    parser: impl Parser<&'a str, Output = O, Error = nom::error::Error<&'a str>>,
+) -> impl Parser<&'a str, Output = O, Error = nom::error::Error<&'a str>> {
+    preceded(multispace0, parser)
+}
+
+// Only the last component of a pattern may contain wildcards, which
+// is enough for things like assets/hrh/*[02468].mhtml.  Hidden files
+// only match a pattern that starts with a dot.
+fn expand(pattern: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+    let file_pattern = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if !file_pattern.contains(['*', '?', '[']) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_pattern =
+        Pattern::new(&file_pattern).with_context(|| format!("Bad pattern {pattern:?}"))?;
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if matches(&file_pattern, &entry.file_name().to_string_lossy()) {
+            paths.push(entry.path());
+        }
+    }
+    if paths.is_empty() {
+        bail!("Nothing matches {}", path.display());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn matches(pattern: &Pattern, name: &str) -> bool {
+    pattern.matches_with(
+        name,
+        MatchOptions {
+            require_literal_leading_dot: true,
+            ..MatchOptions::new()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(name: &str) -> Box<Expression> {
+        Box::new(Expression::Group(name.to_string()))
+    }
+
+    #[test]
+    fn test_expression() {
+        use Expression::*;
+
+        assert_eq!(
+            "cw & ccw".parse::<Expression>().unwrap(),
+            Intersection(group("cw"), group("ccw"))
+        );
+        assert_eq!(
+            "a | b & c - d".parse::<Expression>().unwrap(),
+            Difference(
+                Box::new(Union(
+                    group("a"),
+                    Box::new(Intersection(group("b"), group("c")))
+                )),
+                group("d")
+            )
+        );
+        assert_eq!(
+            "atleast(2, a, (b|c))".parse::<Expression>().unwrap(),
+            AtLeast(2, vec![*group("a"), Union(group("b"), group("c"))])
+        );
+        assert!("a &".parse::<Expression>().is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let matches = |pattern: &str, name: &str| matches(&Pattern::new(pattern).unwrap(), name);
+
+        assert!(matches("*[02468].mhtml", "HRH 1992.mhtml"));
+        assert!(!matches("*[02468].mhtml", "HRH 1993.mhtml"));
+        assert!(matches("*[!0-8].mhtml", "HRH 1999.mhtml"));
+        assert!(!matches("*[!0-8].mhtml", "HRH 1998.mhtml"));
+        assert!(matches("[a-c]*.json", "b.json"));
+        assert!(!matches("[a-c]*.json", "d.json"));
+        assert!(matches("[]]*", "]x"));
+        assert!(matches("20?2.json", "2012.json"));
+        assert!(!matches("20?2.json", "2012.mhtml"));
+        assert!(!matches("20?2.json", "202.json"));
+        assert!(matches("**", "2012.json"));
+        assert!(!matches("*", ".DS_Store"));
+        assert!(matches(".*", ".DS_Store"));
+        // Backtracking is linear, so this doesn't take forever.
+        assert!(!matches(&"*a".repeat(30), &"a".repeat(29)));
+        assert!(Pattern::new("[a-").is_err());
+    }
+}
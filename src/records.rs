@@ -0,0 +1,386 @@
+// All-time records for a race with decades of results, given a
+// directory with one results file per year, like streaks (e.g.,
+// assets/lt100, assets/wser or assets/quad): the top performances for
+// each sex and for each sex and age group (see --age-groups), how the
+// course record for each sex progressed and each year's winners.  A
+// runner can be on a top list more than once, since these are
+// performances, not people.
+//
+// A race whose course changed isn't comparable across the change, so
+// when sidecars (see details.rs) give the years a course, each course
+// gets its own records.  Years without a course are one course, too.
+//
+// Some results only give a finisher's sex in their category (e.g., the
+// quad's MALE 45-49), so when the parser doesn't give a sex, it's
+// worked out from the event.
+
+use {
+    crate::{
+        Duration, Loader, MaleOrFemale,
+        age_groups::AgeGroups,
+        divisions, names,
+        output::{Output, Times, ranks, seconds, write_json, write_table},
+        streaks::paths_by_year,
+    },
+    anyhow::Result,
+    serde::Serialize,
+    std::{borrow::Cow, path::Path},
+};
+
+#[derive(Clone, Debug, Serialize)]
+struct Performance {
+    year: u16,
+    name: String,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+    sex: MaleOrFemale,
+    age: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct TopList {
+    sex: MaleOrFemale,
+    /// None for the list of everyone of the sex
+    age_group: Option<String>,
+    performances: Vec<Ranked>,
+}
+
+#[derive(Serialize)]
+struct Ranked {
+    rank: usize,
+    #[serde(flatten)]
+    performance: Performance,
+}
+
+#[derive(Serialize)]
+struct Records {
+    /// None for the years whose course isn't given
+    course: Option<String>,
+    first: u16,
+    last: u16,
+    top: Vec<TopList>,
+    /// each year the course record was set or broken
+    progression: Vec<Performance>,
+    winners: Vec<Performance>,
+}
+
+const SEXES: [MaleOrFemale; 3] = [
+    MaleOrFemale::Male,
+    MaleOrFemale::Female,
+    MaleOrFemale::NonBinary,
+];
+
+pub(crate) fn summarize(
+    directory: &Path,
+    top: usize,
+    age_groups: &AgeGroups,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let paths = paths_by_year(directory)?.into_iter().collect::<Vec<_>>();
+    let races = crate::load_in_order(&paths, |(_, path)| loader.results(path))?;
+
+    // Courses, in the order they were first run, and each one's
+    // performances, in year order.
+    let mut courses: Vec<(Option<String>, Vec<Performance>)> = vec![];
+    for ((year, _), race) in paths.iter().zip(&races) {
+        loader.check(race)?;
+        let course = race.details.course.clone();
+        let index = match courses.iter().position(|(c, _)| *c == course) {
+            Some(index) => index,
+            None => {
+                courses.push((course, vec![]));
+                courses.len() - 1
+            }
+        };
+        courses[index]
+            .1
+            .extend(race.finishers.iter().filter_map(|finisher| {
+                Some(Performance {
+                    year: *year,
+                    name: names::canonical(Cow::from(&finisher.name)).into_owned(),
+                    time: finisher.time,
+                    sex: finisher
+                        .morf
+                        .or_else(|| divisions::implied_sex(finisher.event.as_deref()?))?,
+                    age: finisher.age,
+                })
+            }));
+    }
+    let records = courses
+        .into_iter()
+        .filter(|(_, performances)| !performances.is_empty())
+        .map(|(course, performances)| records(course, performances, top, age_groups))
+        .collect::<Vec<_>>();
+
+    match output {
+        Output::Text => print(&records),
+        Output::Json => write_json(&records)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            [
+                "course",
+                "list",
+                "sex",
+                "age group",
+                "rank",
+                "year",
+                "name",
+                "time",
+                "age",
+            ]
+            .map(String::from)
+            .to_vec(),
+            records.iter().flat_map(|records| {
+                let row = move |list: &str,
+                                age_group: Option<&str>,
+                                rank: Option<usize>,
+                                performance: &Performance| {
+                    vec![
+                        records.course.clone().unwrap_or_default(),
+                        list.to_string(),
+                        performance.sex.to_string(),
+                        age_group.unwrap_or_default().to_string(),
+                        rank.map(|rank| rank.to_string()).unwrap_or_default(),
+                        performance.year.to_string(),
+                        performance.name.clone(),
+                        times.format(performance.time),
+                        performance
+                            .age
+                            .map(|age| age.to_string())
+                            .unwrap_or_default(),
+                    ]
+                };
+                let top = records.top.iter().flat_map(move |list| {
+                    list.performances.iter().map(move |ranked| {
+                        row(
+                            "top",
+                            list.age_group.as_deref(),
+                            Some(ranked.rank),
+                            &ranked.performance,
+                        )
+                    })
+                });
+                let progression = records
+                    .progression
+                    .iter()
+                    .map(move |performance| row("record", None, None, performance));
+                let winners = records
+                    .winners
+                    .iter()
+                    .map(move |performance| row("winner", None, None, performance));
+                top.chain(progression).chain(winners)
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+fn records(
+    course: Option<String>,
+    mut performances: Vec<Performance>,
+    top: usize,
+    age_groups: &AgeGroups,
+) -> Records {
+    let first = performances
+        .iter()
+        .map(|p| p.year)
+        .min()
+        .unwrap_or_default();
+    let last = performances
+        .iter()
+        .map(|p| p.year)
+        .max()
+        .unwrap_or_default();
+
+    // Each year's winners, and the record after each year.
+    let mut winners = vec![];
+    let mut progression: Vec<Performance> = vec![];
+    for sex in SEXES {
+        for year in first..=last {
+            let Some(winner) = performances
+                .iter()
+                .filter(|p| p.sex == sex && p.year == year)
+                .min_by_key(|p| p.time)
+            else {
+                continue;
+            };
+            winners.push(winner.clone());
+            let record = progression.iter().rfind(|p| p.sex == sex);
+            if record.is_none_or(|record| winner.time < record.time) {
+                progression.push(winner.clone());
+            }
+        }
+    }
+    winners.sort_by_key(|p| (p.year, p.sex));
+
+    performances.sort_by(|p1, p2| (p1.time, p1.year, &p1.name).cmp(&(p2.time, p2.year, &p2.name)));
+    let age_group = |age| age_groups.group(age);
+    let mut lists = vec![];
+    for sex in SEXES {
+        let mut groups = performances
+            .iter()
+            .filter(|p| p.sex == sex)
+            .filter_map(|p| p.age.map(age_group))
+            .collect::<Vec<_>>();
+        groups.sort_by_key(|group| group.0);
+        groups.dedup();
+        for group in [None].into_iter().chain(groups.into_iter().map(Some)) {
+            let list = performances
+                .iter()
+                .filter(|p| {
+                    p.sex == sex
+                        && group
+                            .as_ref()
+                            .is_none_or(|group| p.age.map(age_group).as_ref() == Some(group))
+                })
+                .collect::<Vec<_>>();
+            if list.is_empty() {
+                continue;
+            }
+            let ranks = ranks(&list, |p| p.time);
+            lists.push(TopList {
+                sex,
+                age_group: group.map(|(_, label)| label),
+                performances: list
+                    .into_iter()
+                    .zip(ranks)
+                    .take_while(|(_, rank)| *rank <= top)
+                    .map(|(performance, rank)| Ranked {
+                        rank,
+                        performance: performance.clone(),
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    Records {
+        course,
+        first,
+        last,
+        top: lists,
+        progression,
+        winners,
+    }
+}
+
+fn print(records: &[Records]) {
+    let mut need_nl = false;
+    let mut heading = |heading: String| {
+        if need_nl {
+            println!();
+        }
+        need_nl = true;
+        println!("{heading}");
+    };
+    for records in records {
+        let course = match &records.course {
+            None => String::new(),
+            Some(course) => format!(" on the {course} course"),
+        };
+        let years = format!("{}-{}", records.first, records.last);
+        for list in &records.top {
+            heading(match &list.age_group {
+                None => format!("Top {}{course}, {years}", list.sex),
+                Some(group) => format!("Top {} {group}{course}, {years}", list.sex),
+            });
+            for Ranked { rank, performance } in &list.performances {
+                print_performance(Some(*rank), performance);
+            }
+        }
+        for sex in SEXES {
+            if records.progression.iter().any(|p| p.sex == sex) {
+                heading(format!("{sex} course record{course}"));
+                for performance in records.progression.iter().filter(|p| p.sex == sex) {
+                    print_performance(None, performance);
+                }
+            }
+        }
+        heading(format!("Winners{course}"));
+        for performance in &records.winners {
+            print_performance(None, performance);
+        }
+    }
+}
+
+fn print_performance(rank: Option<usize>, performance: &Performance) {
+    let Performance {
+        year,
+        name,
+        time,
+        sex,
+        age,
+    } = performance;
+    println!(
+        "{:>4} {year} {sex} {time:>10.1} {name}{}",
+        rank.map(|rank| rank.to_string()).unwrap_or_default(),
+        age.map(|age| format!(" ({age})")).unwrap_or_default()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, MaleOrFemale::*};
+
+    #[test]
+    fn test_records() {
+        let performance = |year, name: &str, secs, sex, age| Performance {
+            year,
+            name: name.to_string(),
+            time: Duration::new(secs, 0),
+            sex,
+            age,
+        };
+        let records = records(
+            None,
+            vec![
+                performance(2001, "A", 100, Male, Some(41)),
+                performance(2001, "B", 120, Female, Some(35)),
+                performance(2001, "C", 130, Male, Some(45)),
+                performance(2002, "A", 105, Male, Some(42)),
+                performance(2002, "B", 110, Female, None),
+                performance(2003, "D", 95, Male, Some(19)),
+            ],
+            2,
+            &AgeGroups::default(),
+        );
+        let names = |performances: &[Performance]| {
+            performances
+                .iter()
+                .map(|p| format!("{} {} {}", p.year, p.sex, p.name))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&records.progression),
+            ["2001 M A", "2003 M D", "2001 F B", "2002 F B"]
+        );
+        assert_eq!(
+            names(&records.winners),
+            ["2001 M A", "2001 F B", "2002 M A", "2002 F B", "2003 M D"]
+        );
+        let lists = records
+            .top
+            .iter()
+            .map(|list| {
+                let names = list
+                    .performances
+                    .iter()
+                    .map(|ranked| ranked.performance.name.as_str())
+                    .collect::<String>();
+                format!("{} {:?} {names}", list.sex, list.age_group)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lists,
+            [
+                "M None DA",
+                "M Some(\"under 20\") D",
+                "M Some(\"40-49\") AA",
+                "F None BB",
+                "F Some(\"30-39\") B",
+            ]
+        );
+    }
+}
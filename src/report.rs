@@ -0,0 +1,485 @@
+// Generates a static web site for a series directory (one directory
+// per category, one file per race), so that the standings can be
+// uploaded as-is rather than pasted in by hand.  There's an overall
+// standings page, a page per category, a page per race (with its date,
+// distance and so on, if it has details) and a page per runner showing
+// which races contributed points.  Everything,
+// including the table sorting, is in the pages themselves, so there
+// are no external assets.
+
+use {
+    crate::{
+        Category as SeriesCategory, Loader, RaceDetails, RaceInfo, ScoreInfo, Scoring, file_name,
+        output::ranks, score_directories, series_races,
+    },
+    anyhow::Result,
+    htmlescape::encode_minimal as escape,
+    std::{
+        cmp::Reverse,
+        collections::{HashMap, HashSet},
+        fmt::Write,
+        fs,
+        path::Path,
+    },
+};
+
+pub(crate) fn generate(
+    series: &Path,
+    out: &Path,
+    scoring: &Scoring,
+    loader: &Loader,
+) -> Result<()> {
+    let categories = loader.series(series)?;
+    categories
+        .iter()
+        .flat_map(|category| &category.races)
+        .try_for_each(|race| loader.check(race))?;
+    let scores = score_directories(&categories, &scoring.resolve(Some(series))?);
+    let report = Report::new(file_name(series), &categories, scores);
+
+    for directory in ["categories", "races", "runners"] {
+        fs::create_dir_all(out.join(directory))?;
+    }
+    fs::write(out.join("index.html"), report.index()?)?;
+    for (i, category) in report.categories.iter().enumerate() {
+        fs::write(
+            out.join("categories")
+                .join(format!("{}.html", category.slug)),
+            report.category(i)?,
+        )?;
+    }
+    for (i, race) in report.races.iter().enumerate() {
+        fs::write(
+            out.join("races").join(format!("{}.html", race.slug)),
+            report.race(i)?,
+        )?;
+    }
+    for runner in &report.runners {
+        fs::write(
+            out.join("runners").join(format!("{}.html", runner.slug)),
+            report.runner(runner)?,
+        )?;
+    }
+    Ok(())
+}
+
+struct Category {
+    name: String,
+    slug: String,
+}
+
+struct Race {
+    name: String,
+    slug: String,
+    category: usize,
+    details: RaceDetails,
+}
+
+struct Runner {
+    rank: usize,
+    name: String,
+    slug: String,
+    points: u32,
+    count: usize,
+    // one per category the runner has points in
+    scores: Vec<ScoreInfo>,
+}
+
+struct Report {
+    title: String,
+    categories: Vec<Category>,
+    races: Vec<Race>,
+    runners: Vec<Runner>,
+}
+
+impl Report {
+    fn new(
+        title: String,
+        categories: &[SeriesCategory],
+        scores: HashMap<String, Vec<ScoreInfo>>,
+    ) -> Self {
+        let mut slugs = Slugs::default();
+        let races = series_races(categories)
+            .into_iter()
+            .map(|(category, race)| {
+                let name = race.name();
+                Race {
+                    slug: slugs.unique(&name),
+                    name,
+                    category,
+                    details: race.details.clone(),
+                }
+            })
+            .collect();
+        let categories = categories
+            .iter()
+            .map(|category| Category {
+                slug: slugs.unique(&category.name),
+                name: category.name.clone(),
+            })
+            .collect();
+
+        let mut runners = scores
+            .into_iter()
+            .map(|(name, scores)| {
+                // Totals are wider than each category's points and
+                // count, which a long series can overflow.
+                let points = scores.iter().map(|s| u32::from(s.points)).sum::<u32>();
+                let count = scores.iter().map(|s| usize::from(s.count)).sum::<usize>();
+                (name, points, count, scores)
+            })
+            .collect::<Vec<_>>();
+        runners.sort_by(|(name1, points1, count1, _), (name2, points2, count2, _)| {
+            (Reverse((points1, count1)), name1).cmp(&(Reverse((points2, count2)), name2))
+        });
+        let ranks = ranks(&runners, |(_, points, _, _)| *points);
+        let mut slugs = Slugs::default();
+        let runners = runners
+            .into_iter()
+            .zip(ranks)
+            .map(|((name, points, count, scores), rank)| Runner {
+                rank,
+                slug: slugs.unique(&name),
+                name,
+                points,
+                count,
+                scores,
+            })
+            .collect();
+
+        Self {
+            title,
+            categories,
+            races,
+            runners,
+        }
+    }
+
+    fn category_of(&self, score: &ScoreInfo) -> usize {
+        self.races[score.path_index as usize].category
+    }
+
+    fn index(&self) -> Result<String> {
+        let mut body = format!("<h1>{}</h1>\n", escape(&self.title));
+        body.push_str("<table class=\"sortable\">\n<thead><tr><th>Rank</th><th>Runner</th><th>Points</th><th>Races</th>");
+        for category in &self.categories {
+            write!(
+                body,
+                "<th><a href=\"categories/{}.html\">{}</a></th>",
+                category.slug,
+                escape(&category.name)
+            )?;
+        }
+        body.push_str("</tr></thead>\n<tbody>\n");
+        for runner in &self.runners {
+            write!(
+                body,
+                "<tr><td>{}</td><td><a href=\"runners/{}.html\">{}</a></td><td>{}</td><td>{}</td>",
+                runner.rank,
+                runner.slug,
+                escape(&runner.name),
+                runner.points,
+                runner.count
+            )?;
+            for i in 0..self.categories.len() {
+                match runner.scores.iter().find(|s| self.category_of(s) == i) {
+                    None => body.push_str("<td></td>"),
+                    Some(score) => {
+                        let race = &self.races[score.path_index as usize];
+                        write!(
+                            body,
+                            "<td><a href=\"races/{}.html\" title=\"{}\">{}</a></td>",
+                            race.slug,
+                            escape(&race.name),
+                            score.points
+                        )?;
+                    }
+                }
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</tbody>\n</table>\n");
+        Ok(page(&self.title, "", &body))
+    }
+
+    fn category(&self, i: usize) -> Result<String> {
+        let category = &self.categories[i];
+        let mut body = format!(
+            "<p><a href=\"../index.html\">{}</a></p>\n<h1>{}</h1>\n<ul>\n",
+            escape(&self.title),
+            escape(&category.name)
+        );
+        for race in self.races.iter().filter(|race| race.category == i) {
+            write!(
+                body,
+                "<li><a href=\"../races/{}.html\">{}</a>",
+                race.slug,
+                escape(&race.name)
+            )?;
+            if let Some(date) = &race.details.date {
+                write!(body, " ({})", escape(date))?;
+            }
+            body.push_str("</li>\n");
+        }
+        body.push_str("</ul>\n<table class=\"sortable\">\n<thead><tr><th>Rank</th><th>Runner</th><th>Points</th><th>Race</th><th>Races</th></tr></thead>\n<tbody>\n");
+
+        let mut scores = self
+            .runners
+            .iter()
+            .filter_map(|runner| {
+                runner
+                    .scores
+                    .iter()
+                    .find(|s| self.category_of(s) == i)
+                    .map(|score| (runner, score))
+            })
+            .collect::<Vec<_>>();
+        scores.sort_by_key(|(runner, score)| (Reverse(score.points), &runner.name));
+        let ranks = ranks(&scores, |(_, score)| score.points);
+        for ((runner, score), rank) in scores.into_iter().zip(ranks) {
+            let race = &self.races[score.path_index as usize];
+            writeln!(
+                body,
+                "<tr><td>{rank}</td><td><a href=\"../runners/{}.html\">{}</a></td><td>{}</td><td><a href=\"../races/{}.html\">{}</a></td><td>{}</td></tr>",
+                runner.slug,
+                escape(&runner.name),
+                score.points,
+                race.slug,
+                escape(&race.name),
+                score.count
+            )?;
+        }
+        body.push_str("</tbody>\n</table>\n");
+        Ok(page(&category.name, "../", &body))
+    }
+
+    fn race(&self, i: usize) -> Result<String> {
+        let race = &self.races[i];
+        let category = &self.categories[race.category];
+        let mut body = format!(
+            "<p><a href=\"../index.html\">{}</a> / <a href=\"../categories/{}.html\">{}</a></p>\n<h1>{}</h1>\n",
+            escape(&self.title),
+            category.slug,
+            escape(&category.name),
+            escape(&race.name)
+        );
+        if let Some(about) = about(&race.details) {
+            writeln!(body, "<p>{}</p>", escape(&about))?;
+        }
+        body.push_str("<table class=\"sortable\">\n<thead><tr><th>Place</th><th>Runner</th><th>Sex</th><th>Time</th><th>Points</th><th>Counted</th></tr></thead>\n<tbody>\n");
+
+        let mut results = self
+            .runners
+            .iter()
+            .flat_map(|runner| {
+                runner.scores.iter().flat_map(move |score| {
+                    score
+                        .races
+                        .iter()
+                        .filter(move |r| r.path_index as usize == i)
+                        .map(move |r| (runner, score, r))
+                })
+            })
+            .collect::<Vec<_>>();
+        results.sort_by_key(|(runner, _, r)| (r.time, &runner.name));
+        for (place, (runner, score, r)) in results.into_iter().enumerate() {
+            writeln!(
+                body,
+                "<tr><td>{}</td><td><a href=\"../runners/{}.html\">{}</a></td><td>{}</td><td data-value=\"{}\">{:.1}</td><td>{}</td><td>{}</td></tr>",
+                place + 1,
+                runner.slug,
+                escape(&runner.name),
+                r.morf,
+                r.time.as_secs_f64(),
+                r.time,
+                r.points,
+                counted(score, r)
+            )?;
+        }
+        body.push_str("</tbody>\n</table>\n");
+        Ok(page(&race.name, "../", &body))
+    }
+
+    fn runner(&self, runner: &Runner) -> Result<String> {
+        let mut body = format!(
+            "<p><a href=\"../index.html\">{}</a></p>\n<h1>{}</h1>\n<p>Rank {} with {} points from {} races.</p>\n",
+            escape(&self.title),
+            escape(&runner.name),
+            runner.rank,
+            runner.points,
+            runner.count
+        );
+        body.push_str("<table class=\"sortable\">\n<thead><tr><th>Category</th><th>Race</th><th>Time</th><th>Points</th><th>Counted</th></tr></thead>\n<tbody>\n");
+
+        let mut results = runner
+            .scores
+            .iter()
+            .flat_map(|score| score.races.iter().map(move |r| (score, r)))
+            .collect::<Vec<_>>();
+        results.sort_by_key(|(_, r)| r.path_index);
+        for (score, r) in results {
+            let race = &self.races[r.path_index as usize];
+            let category = &self.categories[race.category];
+            writeln!(
+                body,
+                "<tr><td><a href=\"../categories/{}.html\">{}</a></td><td><a href=\"../races/{}.html\">{}</a></td><td data-value=\"{}\">{:.1}</td><td>{}</td><td>{}</td></tr>",
+                category.slug,
+                escape(&category.name),
+                race.slug,
+                escape(&race.name),
+                r.time.as_secs_f64(),
+                r.time,
+                r.points,
+                counted(score, r)
+            )?;
+        }
+        body.push_str("</tbody>\n</table>\n");
+        Ok(page(&runner.name, "../", &body))
+    }
+}
+
+// e.g., "2025-02-15, 5 km, road", or None if nothing's known.
+fn about(details: &RaceDetails) -> Option<String> {
+    let distance = details.distance.as_ref().map(|distance| {
+        match details.units {
+            // A distance without units is in units.
+            Some(units) if distance.parse::<f64>().is_ok() => format!("{distance} {units}"),
+            _ => distance.clone(),
+        }
+    });
+    let about = [
+        details.date.clone(),
+        distance,
+        details.surface.map(|surface| surface.to_string()),
+        details
+            .course
+            .as_ref()
+            .map(|course| format!("{course} course")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    (!about.is_empty()).then(|| about.join(", "))
+}
+
+// Only the race with the most points in each category counts toward
+// the total.
+fn counted(score: &ScoreInfo, race: &RaceInfo) -> &'static str {
+    if score.path_index == race.path_index {
+        "&#10003;"
+    } else {
+        ""
+    }
+}
+
+#[derive(Default)]
+struct Slugs(HashSet<String>);
+
+impl Slugs {
+    fn unique(&mut self, name: &str) -> String {
+        let slug = slug(name);
+        let mut candidate = slug.clone();
+        let mut n = 1;
+        while !self.0.insert(candidate.clone()) {
+            n += 1;
+            candidate = format!("{slug}-{n}");
+        }
+        candidate
+    }
+}
+
+fn slug(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "unnamed".to_string()
+    } else {
+        slug
+    }
+}
+
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{}</title>
+<style>
+body {{ font-family: sans-serif; margin: 1em 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.2em 0.6em; text-align: right; }}
+th:nth-child(2), td:nth-child(2) {{ text-align: left; }}
+tbody tr:nth-child(odd) {{ background: #f0f0f0; }}
+th {{ cursor: pointer; border-bottom: 1px solid #888; }}
+th[data-order="ascending"]::after {{ content: " \25B2"; }}
+th[data-order="descending"]::after {{ content: " \25BC"; }}
+</style>
+</head>
+<body>
+{body}<p><small><a href="{root}index.html">standings</a></small></p>
+<script>
+for (const th of document.querySelectorAll("table.sortable th")) {{
+  th.addEventListener("click", () => {{
+    const column = th.cellIndex;
+    const ascending = th.dataset.order !== "ascending";
+    for (const other of th.parentNode.children) delete other.dataset.order;
+    th.dataset.order = ascending ? "ascending" : "descending";
+    const value = row => {{
+      const cell = row.cells[column];
+      return cell.dataset.value ?? cell.textContent.trim();
+    }};
+    const tbody = th.closest("table").tBodies[0];
+    const rows = Array.from(tbody.rows);
+    rows.sort((a, b) => {{
+      const x = value(a), y = value(b);
+      if (x === "" || y === "") return (x === "") - (y === "");
+      const order = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+      return ascending ? order : -order;
+    }});
+    tbody.append(...rows);
+  }});
+}}
+</script>
+</body>
+</html>
+"#,
+        escape(title)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugs() {
+        let mut slugs = Slugs::default();
+        assert_eq!(slugs.unique("Mt. Taylor 50k 2022"), "mt-taylor-50k-2022");
+        assert_eq!(slugs.unique("Chris Bratton"), "chris-bratton");
+        assert_eq!(slugs.unique("chris bratton"), "chris-bratton-2");
+        assert_eq!(slugs.unique("!!!"), "unnamed");
+    }
+
+    #[test]
+    fn test_about() {
+        assert_eq!(about(&RaceDetails::default()), None);
+        let details = RaceDetails {
+            date: Some("2025-02-15".to_string()),
+            distance: Some("5".to_string()),
+            units: Some(crate::Units::Km),
+            surface: Some(crate::Surface::Road),
+            course: Some("2019".to_string()),
+            ..RaceDetails::default()
+        };
+        assert_eq!(
+            about(&details).as_deref(),
+            Some("2025-02-15, 5 km, road, 2019 course")
+        );
+    }
+}
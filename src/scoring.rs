@@ -0,0 +1,316 @@
+// How category and series modes turn a race's finishers into points.
+// The ABQ Road Runners' way (see the README) is percent of the
+// winner's time, so the winner gets 100 and someone who takes twice as
+// long gets 50, with men and women scored separately.  A winner who's
+// far faster than everyone else compresses everyone else's scores,
+// though, so points can instead be percent of the median time, where
+// a typical finisher gets 100 and the winner gets more.  Many club
+// series instead give points by place, from a table (e.g., 25 for
+// first, 20 for second, 16 for third and so on) or by how many finished
+// behind the runner.
+//
+// Whichever it is, finishers are scored against everyone of their sex
+// (the default, which leaves out finishers whose sex isn't M or F),
+// everyone of their sex and age group (ten years each, unless
+// --age-groups says otherwise), or everyone whose sex is known.
+// Points from times are rounded down (the default) or to the nearest
+// point.
+
+use {
+    crate::{
+        Duration, Finisher, MaleOrFemale, RaceResults, age_groups::AgeGroups, manifest,
+        stats::median,
+    },
+    anyhow::{Result, anyhow, bail},
+    clap::ValueEnum,
+    std::{path::Path, str::FromStr},
+};
+
+/// How each race's finishers are scored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Scoring {
+    pub method: Method,
+    pub pools: Pools,
+    pub rounding: Rounding,
+    /// the points for each place, for Method::Places
+    pub points: Points,
+    /// the age groups, for Pools::Division
+    pub age_groups: AgeGroups,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Method {
+    /// the winner's time divided by the finisher's, as a percentage
+    #[default]
+    Winner,
+    /// the median time divided by the finisher's, as a percentage
+    Median,
+    /// points for the finisher's place, from --points
+    Places,
+}
+
+/// Who a finisher is scored against.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Pools {
+    /// everyone whose sex is known
+    Everyone,
+    /// everyone of the same sex, for men and women
+    #[default]
+    Sex,
+    /// everyone of the same sex and age group (see --age-groups), for
+    /// men and women, with finishers whose age isn't known in a group
+    /// of their own
+    Division,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Rounding {
+    /// to the point below
+    #[default]
+    Down,
+    /// to the nearest point
+    Nearest,
+}
+
+/// Points by place: field, grand-prix (25, 20, 16, 13, 11, 10 and so
+/// on down to 1 for 15th), a list like 25,20,16 or the name of a table
+/// in a series.toml.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Points {
+    /// how many finished behind the runner, so last place gets none
+    Field,
+    /// the points for first, second and so on; everyone else gets none
+    Table(Vec<u16>),
+    /// a table in series.toml, which has to be looked up before scoring
+    Named(String),
+}
+
+const GRAND_PRIX: [u16; 15] = [25, 20, 16, 13, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
+
+impl Default for Points {
+    fn default() -> Self {
+        Points::Table(GRAND_PRIX.to_vec())
+    }
+}
+
+impl FromStr for Points {
+    type Err = anyhow::Error;
+
+    fn from_str(points: &str) -> Result<Self> {
+        let points = points.trim();
+        Ok(match points {
+            "field" => Points::Field,
+            "grand-prix" => Points::default(),
+            _ if points.starts_with(|c: char| c.is_ascii_digit()) => Points::Table(
+                points
+                    .split(',')
+                    .map(|p| {
+                        p.trim()
+                            .parse()
+                            .map_err(|_| anyhow!("{p:?} isn't a number of points"))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            "" => bail!("points must be field, grand-prix, a list like 25,20,16 or a table name"),
+            _ => Points::Named(points.to_string()),
+        })
+    }
+}
+
+// The pool a finisher is scored in: their sex, if pools are by sex,
+// and their age group, if pools are by division.
+type Pool = (Option<MaleOrFemale>, Option<usize>);
+
+impl Scoring {
+    /// The scoring with its points table, if it's named, looked up in
+    /// series' series.toml, which is an error if there's no such table.
+    pub(crate) fn resolve(&self, series: Option<&Path>) -> Result<Scoring> {
+        let Points::Named(name) = &self.points else {
+            return Ok(self.clone());
+        };
+        let mut tables = match series {
+            None => Default::default(),
+            Some(series) => manifest::points_tables(series)?,
+        };
+        let table = tables.remove(name).ok_or_else(|| {
+            anyhow!(
+                "No points table {name:?}; tables are named in a series' {}",
+                manifest::FILE_NAME
+            )
+        })?;
+        Ok(Scoring {
+            points: Points::Table(table),
+            ..self.clone()
+        })
+    }
+
+    /// Everyone in race who's scored, fastest first, with their sex and
+    /// points.
+    pub(crate) fn points<'a>(
+        &self,
+        race: &'a RaceResults,
+    ) -> Vec<(&'a Finisher, MaleOrFemale, u16)> {
+        let mut finishers = race
+            .finishers
+            .iter()
+            .filter_map(|finisher| Some((finisher, finisher.morf?)))
+            .filter(|&(_, morf)| self.pools == Pools::Everyone || morf != MaleOrFemale::NonBinary)
+            .collect::<Vec<_>>();
+        finishers.sort_by_key(|(finisher, _)| finisher.time);
+
+        let pool = |finisher: &Finisher, morf| -> Pool {
+            match self.pools {
+                Pools::Everyone => (None, None),
+                Pools::Sex => (Some(morf), None),
+                Pools::Division => (
+                    Some(morf),
+                    finisher.age.map(|age| self.age_groups.group(age).0),
+                ),
+            }
+        };
+        // Each pool's times, which are in order since the finishers are.
+        let mut pools: Vec<(Pool, Vec<Duration>)> = vec![];
+        for &(finisher, morf) in &finishers {
+            let key = pool(finisher, morf);
+            match pools.iter_mut().find(|(p, _)| *p == key) {
+                Some((_, times)) => times.push(finisher.time),
+                None => pools.push((key, vec![finisher.time])),
+            }
+        }
+
+        finishers
+            .into_iter()
+            .map(|(finisher, morf)| {
+                let key = pool(finisher, morf);
+                let times = &pools.iter().find(|(p, _)| *p == key).unwrap().1;
+                (finisher, morf, self.score(finisher.time, times))
+            })
+            .collect()
+    }
+
+    // The points for time, given the times of everyone in its pool.
+    fn score(&self, time: Duration, times: &[Duration]) -> u16 {
+        match self.method {
+            Method::Winner => self.round(times[0].as_secs() as f64, time.as_secs()),
+            Method::Median => self.round(median(times).unwrap().into(), time.as_secs()),
+            Method::Places => {
+                // Finishers who tie share a place.
+                let place = times.partition_point(|&t| t < time) + 1;
+                match &self.points {
+                    Points::Field => (times.len() - place) as u16,
+                    Points::Table(table) => table.get(place - 1).copied().unwrap_or(0),
+                    // resolve() looks tables up before scoring.
+                    Points::Named(_) => 0,
+                }
+            }
+        }
+    }
+
+    fn round(&self, reference: f64, secs: u64) -> u16 {
+        if secs == 0 {
+            return 100;
+        }
+        let points = reference * 100.0 / secs as f64;
+        (match self.rounding {
+            Rounding::Down => points.floor(),
+            Rounding::Nearest => points.round(),
+        }) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, MaleOrFemale::*};
+
+    fn race(finishers: &[(&str, u64, MaleOrFemale, Option<u8>)]) -> RaceResults {
+        RaceResults {
+            path: "race.json".into(),
+            finishers: finishers
+                .iter()
+                .map(|&(name, secs, morf, age)| Finisher {
+                    name: name.to_string(),
+                    time: Duration::new(secs, 0),
+                    morf: Some(morf),
+                    event: None,
+                    pace: None,
+                    age,
+                })
+                .collect(),
+            warnings: vec![],
+            details: Default::default(),
+            did_not_finish: None,
+            non_finishers: vec![],
+        }
+    }
+
+    fn points(race: &RaceResults, scoring: &Scoring) -> Vec<String> {
+        scoring
+            .points(race)
+            .into_iter()
+            .map(|(finisher, _, points)| format!("{} {points}", finisher.name))
+            .collect()
+    }
+
+    #[test]
+    fn test_points() {
+        let race = race(&[
+            ("A", 100, Male, None),
+            ("B", 300, Male, None),
+            ("C", 200, Female, None),
+            ("D", 400, Male, None),
+            ("E", 150, NonBinary, None),
+        ]);
+        let mut scoring = Scoring::default();
+        assert_eq!(points(&race, &scoring), ["A 100", "C 100", "B 33", "D 25"]);
+
+        scoring.rounding = Rounding::Nearest;
+        assert_eq!(points(&race, &scoring), ["A 100", "C 100", "B 33", "D 25"]);
+
+        scoring.method = Method::Median;
+        assert_eq!(points(&race, &scoring), ["A 300", "C 100", "B 100", "D 75"]);
+
+        scoring.pools = Pools::Everyone;
+        assert_eq!(
+            points(&race, &scoring),
+            ["A 200", "E 133", "C 100", "B 67", "D 50"]
+        );
+        scoring.rounding = Rounding::Down;
+        assert_eq!(
+            points(&race, &scoring),
+            ["A 200", "E 133", "C 100", "B 66", "D 50"]
+        );
+    }
+
+    #[test]
+    fn test_places() {
+        let race = race(&[
+            ("A", 100, Male, Some(45)),
+            ("B", 110, Male, Some(25)),
+            ("C", 110, Male, Some(42)),
+            ("D", 130, Male, Some(48)),
+            ("E", 120, Female, None),
+        ]);
+        let mut scoring = Scoring {
+            method: Method::Places,
+            points: "3,2,1".parse().unwrap(),
+            ..Scoring::default()
+        };
+        assert_eq!(points(&race, &scoring), ["A 3", "B 2", "C 2", "E 3", "D 0"]);
+
+        scoring.pools = Pools::Division;
+        assert_eq!(points(&race, &scoring), ["A 3", "B 3", "C 2", "E 3", "D 1"]);
+        scoring.age_groups = "40,45".parse().unwrap();
+        assert_eq!(points(&race, &scoring), ["A 3", "B 3", "C 3", "E 3", "D 2"]);
+        scoring.age_groups = AgeGroups::default();
+
+        scoring.points = "field".parse().unwrap();
+        scoring.pools = Pools::Sex;
+        assert_eq!(points(&race, &scoring), ["A 3", "B 2", "C 2", "E 0", "D 0"]);
+
+        assert_eq!("grand-prix".parse::<Points>().unwrap(), Points::default());
+        scoring.points = "club".parse().unwrap();
+        assert_eq!(scoring.points, Points::Named("club".to_string()));
+        assert!(scoring.resolve(None).is_err());
+        assert!("25,twenty".parse::<Points>().is_err());
+    }
+}
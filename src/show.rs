@@ -0,0 +1,160 @@
+// Shows what the parsers found in a single results file, which is the
+// first thing to look at when a race's points or totals look wrong:
+// a name that's spelled differently than in the other races, a sex
+// that wasn't recognized, or a file that no parser recognizes at all.
+// The names are as they appear in the results, before aliases are
+// applied.  The events subcommand is similar, but lists the events or
+// divisions that a file has, so that one can be picked with --event.
+
+use {
+    crate::{
+        Duration, Loader, MaleOrFemale, load_in_order,
+        output::{Output, Times, seconds, write_json, write_table},
+    },
+    anyhow::Result,
+    serde::Serialize,
+    std::path::{Path, PathBuf},
+};
+
+#[derive(Serialize)]
+struct Row<'a> {
+    name: &'a str,
+    #[serde(serialize_with = "seconds")]
+    time: Duration,
+    sex: Option<MaleOrFemale>,
+    event: Option<&'a str>,
+}
+
+pub(crate) fn summarize(path: &Path, output: Output, times: Times, loader: &Loader) -> Result<()> {
+    let race = loader.results(path)?;
+    loader.check(&race)?;
+    if race.finishers.is_empty() {
+        eprintln!("No finishers found in {}", path.display());
+    }
+    let rows = race
+        .finishers
+        .iter()
+        .map(|finisher| Row {
+            name: &finisher.name,
+            time: finisher.time,
+            sex: finisher.morf,
+            event: finisher.event.as_deref(),
+        })
+        .collect::<Vec<_>>();
+    match output {
+        Output::Text => print_rows(&rows),
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            vec![
+                "name".to_string(),
+                "time".to_string(),
+                "sex".to_string(),
+                "event".to_string(),
+            ],
+            rows.iter().map(|row| {
+                vec![
+                    row.name.to_string(),
+                    times.format(row.time),
+                    sex(row.sex),
+                    row.event.unwrap_or_default().to_string(),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+fn sex(morf: Option<MaleOrFemale>) -> String {
+    morf.map(|morf| morf.to_string()).unwrap_or_default()
+}
+
+fn print_rows(rows: &[Row]) {
+    let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+    let time_width = rows
+        .iter()
+        .map(|row| format!("{:.1}", row.time).len())
+        .max()
+        .unwrap_or(0);
+    for row in rows {
+        let sex = sex(row.sex);
+        match row.event {
+            None => println!(
+                "{:>name_width$} {:>time_width$.1} {sex}",
+                row.name, row.time
+            ),
+            Some(event) => println!(
+                "{:>name_width$} {:>time_width$.1} {sex:1} {event}",
+                row.name, row.time
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FileEvents<'a> {
+    file: &'a Path,
+    events: Vec<EventCount<'a>>,
+}
+
+#[derive(Serialize)]
+struct EventCount<'a> {
+    event: &'a str,
+    finishers: usize,
+}
+
+pub(crate) fn events(files: &[PathBuf], output: Output, loader: &Loader) -> Result<()> {
+    let races = load_in_order(files, |file| loader.all_results(file))?;
+    races.iter().try_for_each(|race| loader.check(race))?;
+    let files = races
+        .iter()
+        .map(|race| FileEvents {
+            file: &race.path,
+            events: race
+                .events()
+                .into_iter()
+                .map(|event| EventCount {
+                    event,
+                    finishers: race
+                        .finishers
+                        .iter()
+                        .filter(|finisher| finisher.event.as_deref() == Some(event))
+                        .count(),
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    match output {
+        Output::Text => {
+            for FileEvents { file, events } in &files {
+                println!("{}", file.display());
+                if events.is_empty() {
+                    println!("    (no events)");
+                }
+                let width = events.iter().map(|e| e.event.len()).max().unwrap_or(0);
+                for EventCount { event, finishers } in events {
+                    println!("    {event:width$} {finishers:>4}");
+                }
+            }
+        }
+        Output::Json => write_json(&files)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            vec![
+                "file".to_string(),
+                "event".to_string(),
+                "finishers".to_string(),
+            ],
+            files.iter().flat_map(|FileEvents { file, events }| {
+                events.iter().map(|EventCount { event, finishers }| {
+                    vec![
+                        file.display().to_string(),
+                        event.to_string(),
+                        finishers.to_string(),
+                    ]
+                })
+            }),
+        )?,
+    }
+    Ok(())
+}
@@ -0,0 +1,371 @@
+// Summary statistics for races, overall and for each sex: how many
+// finished, the winning, median and mean times, the spread of times
+// (the 10th, 25th, 75th and 90th percentiles) and how old the
+// finishers were.  How many started, and so the DNF rate, is only
+// known for results that list who didn't finish (UltraSignup's), and
+// only overall, since they count non-finishers by status, not by sex.
+//
+// Given a directory with one results file per year, like streaks, each
+// year is a row, so the rows are a time series of how the race has
+// changed, e.g., whether its field is getting bigger or slower.
+
+use {
+    crate::{
+        Duration, Finisher, Loader, MaleOrFemale, RaceResults, Source,
+        age_groups::AgeGroups,
+        load_in_order,
+        output::{Output, Times, seconds, write_json, write_table},
+        streaks::paths_by_year,
+    },
+    anyhow::Result,
+    serde::Serialize,
+    std::path::Path,
+};
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    /// the race's name, or its year in a time series
+    race: String,
+    /// None for everyone
+    sex: Option<MaleOrFemale>,
+    /// None unless the results say who didn't finish
+    starters: Option<usize>,
+    finishers: usize,
+    /// the fraction of starters who didn't finish
+    dnf_rate: Option<f64>,
+    #[serde(serialize_with = "seconds")]
+    winning: Duration,
+    #[serde(serialize_with = "seconds")]
+    median: Duration,
+    #[serde(serialize_with = "seconds")]
+    mean: Duration,
+    #[serde(serialize_with = "seconds")]
+    p10: Duration,
+    #[serde(serialize_with = "seconds")]
+    p25: Duration,
+    #[serde(serialize_with = "seconds")]
+    p75: Duration,
+    #[serde(serialize_with = "seconds")]
+    p90: Duration,
+    /// of the finishers whose age is known
+    median_age: Option<f64>,
+    ages: Vec<AgeCount>,
+}
+
+#[derive(Debug, Serialize)]
+struct AgeCount {
+    group: String,
+    count: usize,
+}
+
+const SEXES: [Option<MaleOrFemale>; 4] = [
+    None,
+    Some(MaleOrFemale::Male),
+    Some(MaleOrFemale::Female),
+    Some(MaleOrFemale::NonBinary),
+];
+
+pub(crate) fn summarize(
+    sources: &[Source],
+    age_groups: &AgeGroups,
+    output: Output,
+    times: Times,
+    loader: &Loader,
+) -> Result<()> {
+    let labeled_races = match sources {
+        [Source::File(directory)] if directory.is_dir() => by_year(directory, loader)?,
+        _ => load_in_order(sources, |source| loader.source(source))?
+            .into_iter()
+            .map(|race| (race.name(), race))
+            .collect(),
+    };
+    let mut rows = vec![];
+    for (label, race) in &labeled_races {
+        loader.check(race)?;
+        rows.extend(race_stats(label, race, age_groups));
+    }
+    let labels = age_group_labels(age_groups);
+
+    match output {
+        Output::Text => {
+            let label_width = rows.iter().map(|row| row.race.len()).max().unwrap_or(0);
+            println!(
+                "{:label_width$} {:3} {:>5} {:>5} {:>5} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>4}",
+                "",
+                "",
+                "start",
+                "fin",
+                "dnf",
+                "winning",
+                "p10",
+                "p25",
+                "median",
+                "p75",
+                "p90",
+                "mean",
+                "age"
+            );
+            for row in &rows {
+                println!(
+                    "{:label_width$} {:3} {:>5} {:>5} {:>5} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>4}",
+                    row.race,
+                    sex_label(row.sex),
+                    row.starters
+                        .map(|starters| starters.to_string())
+                        .unwrap_or_default(),
+                    row.finishers,
+                    row.dnf_rate
+                        .map(|rate| format!("{:.0}%", rate * 100.0))
+                        .unwrap_or_default(),
+                    row.winning,
+                    row.p10,
+                    row.p25,
+                    row.median,
+                    row.p75,
+                    row.p90,
+                    row.mean,
+                    row.median_age
+                        .map(|age| format!("{age:.0}"))
+                        .unwrap_or_default(),
+                );
+            }
+        }
+        Output::Json => write_json(&rows)?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            [
+                "race",
+                "sex",
+                "starters",
+                "finishers",
+                "dnf rate",
+                "winning",
+                "p10",
+                "p25",
+                "median",
+                "p75",
+                "p90",
+                "mean",
+                "median age",
+            ]
+            .into_iter()
+            .map(String::from)
+            .chain(labels.iter().cloned())
+            .collect(),
+            rows.iter().map(|row| {
+                let mut fields = vec![
+                    row.race.clone(),
+                    sex_label(row.sex),
+                    row.starters
+                        .map(|starters| starters.to_string())
+                        .unwrap_or_default(),
+                    row.finishers.to_string(),
+                    row.dnf_rate
+                        .map(|rate| format!("{rate:.3}"))
+                        .unwrap_or_default(),
+                ];
+                fields.extend(
+                    [
+                        row.winning,
+                        row.p10,
+                        row.p25,
+                        row.median,
+                        row.p75,
+                        row.p90,
+                        row.mean,
+                    ]
+                    .map(|time| times.format(time)),
+                );
+                fields.push(
+                    row.median_age
+                        .map(|age| age.to_string())
+                        .unwrap_or_default(),
+                );
+                fields.extend(labels.iter().map(|group| {
+                    row.ages
+                        .iter()
+                        .find(|count| count.group == *group)
+                        .map_or(0, |count| count.count)
+                        .to_string()
+                }));
+                fields
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+// Each year's results, labeled by year.
+fn by_year(directory: &Path, loader: &Loader) -> Result<Vec<(String, RaceResults)>> {
+    let paths = paths_by_year(directory)?.into_iter().collect::<Vec<_>>();
+    let races = load_in_order(&paths, |(_, path)| loader.results(path))?;
+    Ok(paths
+        .iter()
+        .map(|(year, _)| year.to_string())
+        .zip(races)
+        .collect())
+}
+
+fn sex_label(sex: Option<MaleOrFemale>) -> String {
+    sex.map_or_else(|| "all".to_string(), |sex| sex.to_string())
+}
+
+// Every age group's label, youngest first.
+fn age_group_labels(age_groups: &AgeGroups) -> Vec<String> {
+    let mut labels: Vec<(usize, String)> = (0..=u8::MAX).map(|age| age_groups.group(age)).collect();
+    labels.dedup();
+    labels.into_iter().map(|(_, label)| label).collect()
+}
+
+// A row for everyone and one for each sex that has finishers.
+fn race_stats(label: &str, race: &RaceResults, age_groups: &AgeGroups) -> Vec<Stats> {
+    SEXES
+        .into_iter()
+        .filter_map(|sex| {
+            let finishers = race
+                .finishers
+                .iter()
+                .filter(|finisher| sex.is_none() || finisher.morf == sex)
+                .collect::<Vec<_>>();
+            let did_not_finish = if sex.is_none() {
+                race.did_not_finish
+            } else {
+                None
+            };
+            stats(label, sex, &finishers, did_not_finish, age_groups)
+        })
+        .collect()
+}
+
+// None if nobody finished.
+fn stats(
+    label: &str,
+    sex: Option<MaleOrFemale>,
+    finishers: &[&Finisher],
+    did_not_finish: Option<usize>,
+    age_groups: &AgeGroups,
+) -> Option<Stats> {
+    let mut times = finishers
+        .iter()
+        .map(|finisher| finisher.time)
+        .collect::<Vec<_>>();
+    times.sort();
+    let n = times.len();
+    let starters = did_not_finish.map(|did_not_finish| n + did_not_finish);
+
+    let mut ages = finishers
+        .iter()
+        .filter_map(|finisher| finisher.age)
+        .collect::<Vec<_>>();
+    ages.sort();
+    let mut counts: Vec<((usize, String), usize)> = vec![];
+    for &age in &ages {
+        let group = age_groups.group(age);
+        match counts.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((group, 1)),
+        }
+    }
+    counts.sort();
+
+    Some(Stats {
+        race: label.to_string(),
+        sex,
+        starters,
+        finishers: n,
+        dnf_rate: starters
+            .filter(|&starters| starters > 0)
+            .map(|starters| (starters - n) as f64 / starters as f64),
+        winning: *times.first()?,
+        median: median(&times)?,
+        mean: times.iter().copied().sum::<Duration>() / n as u32,
+        p10: percentile(&times, 10)?,
+        p25: percentile(&times, 25)?,
+        p75: percentile(&times, 75)?,
+        p90: percentile(&times, 90)?,
+        median_age: (!ages.is_empty()).then(|| {
+            let half = ages.len() / 2;
+            if ages.len() % 2 == 1 {
+                f64::from(ages[half])
+            } else {
+                (f64::from(ages[half - 1]) + f64::from(ages[half])) / 2.0
+            }
+        }),
+        ages: counts
+            .into_iter()
+            .map(|((_, group), count)| AgeCount { group, count })
+            .collect(),
+    })
+}
+
+/// The middle time, or the mean of the two middle times, of times that
+/// are in order.  None if there are none.
+pub(crate) fn median(sorted: &[Duration]) -> Option<Duration> {
+    let n = sorted.len();
+    let half_n = n / 2;
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(sorted[half_n]),
+        _ => Some((sorted[half_n - 1] + sorted[half_n]) / 2),
+    }
+}
+
+// The nearest-rank percentile of times that are in order, i.e., the
+// fastest time that's at least as slow as percent of them.
+fn percentile(sorted: &[Duration], percent: usize) -> Option<Duration> {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let finishers = [
+            (100, Some(25)),
+            (300, Some(41)),
+            (200, None),
+            (400, Some(35)),
+        ]
+        .map(|(secs, age)| Finisher {
+            name: "Jane Doe".to_string(),
+            time: Duration::new(secs, 0),
+            morf: Some(MaleOrFemale::Female),
+            event: None,
+            pace: None,
+            age,
+        });
+        let finishers = finishers.iter().collect::<Vec<_>>();
+        let summary = stats("2024", None, &finishers, Some(1), &AgeGroups::default()).unwrap();
+        assert_eq!(summary.starters, Some(5));
+        assert_eq!(summary.dnf_rate, Some(0.2));
+        assert_eq!(summary.winning, Duration::new(100, 0));
+        assert_eq!(summary.median, Duration::new(250, 0));
+        assert_eq!(summary.mean, Duration::new(250, 0));
+        assert_eq!(summary.p10, Duration::new(100, 0));
+        assert_eq!(summary.p75, Duration::new(300, 0));
+        assert_eq!(summary.p90, Duration::new(400, 0));
+        assert_eq!(summary.median_age, Some(35.0));
+        let ages = summary
+            .ages
+            .iter()
+            .map(|count| format!("{} {}", count.group, count.count))
+            .collect::<Vec<_>>();
+        assert_eq!(ages, ["20-29 1", "30-39 1", "40-49 1"]);
+
+        let age_groups = "40".parse::<AgeGroups>().unwrap();
+        let summary = stats("2024", None, &finishers, None, &age_groups).unwrap();
+        let ages = summary
+            .ages
+            .iter()
+            .map(|count| format!("{} {}", count.group, count.count))
+            .collect::<Vec<_>>();
+        assert_eq!(ages, ["under 40 2", "40 and over 1"]);
+        assert_eq!(age_group_labels(&age_groups), ["under 40", "40 and over"]);
+
+        assert!(stats("2024", None, &[], Some(3), &AgeGroups::default()).is_none());
+        assert_eq!(median(&[Duration::new(1, 0)]), Some(Duration::new(1, 0)));
+    }
+}
@@ -0,0 +1,289 @@
+// Streaks are computed over a directory that has one results file per
+// year (e.g., assets/wser or assets/mt_taylor_50k).  The year comes
+// from the file name, so "WSER 1999.mhtml", "lt100_2013.json" and
+// "2022.mhtml" all work, or else from the date in the file's sidecar.
+//
+// A year that has no file breaks everyone's streak, since as far as we
+// know, nobody finished that year.  However, races get cancelled (e.g.,
+// 2020), so years given via --not-held are skipped entirely, rather
+// than breaking streaks.
+
+use {
+    crate::{
+        Loader, details, load_in_order, names,
+        output::{Output, write_json, write_table},
+    },
+    anyhow::{Result, bail},
+    serde::Serialize,
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+pub(crate) fn summarize(
+    directory: &Path,
+    not_held: &[u16],
+    output: Output,
+    loader: &Loader,
+) -> Result<()> {
+    let mut paths = vec![];
+    for (year, path) in paths_by_year(directory)? {
+        if not_held.contains(&year) {
+            eprintln!("{year} was not held, ignoring {}", path.display());
+        } else {
+            paths.push((year, path));
+        }
+    }
+    let races = load_in_order(&paths, |(_, path)| loader.results(path))?;
+
+    let mut finishers = BTreeMap::new();
+    for ((year, path), race) in paths.iter().zip(races) {
+        loader.check(&race)?;
+        if race.finishers.is_empty() {
+            eprintln!("No finishers found in {}", path.display());
+        }
+        let names = race
+            .finishers
+            .into_iter()
+            .map(|finisher| names::canonical(finisher.name.into()).into_owned())
+            .collect();
+        finishers.insert(*year, names);
+    }
+
+    let editions = editions(&finishers, not_held);
+    let mut streaks = streaks(&editions);
+    streaks.sort_by(|(name1, s1), (name2, s2)| {
+        s2.longest
+            .cmp(&s1.longest)
+            .then(s2.current.cmp(&s1.current))
+            .then(name1.cmp(name2))
+    });
+
+    match output {
+        Output::Text => print(&streaks),
+        Output::Json => write_json(
+            &streaks
+                .iter()
+                .map(|(name, streak)| NamedStreak { name, streak })
+                .collect::<Vec<_>>(),
+        )?,
+        Output::Csv | Output::Tsv => write_table(
+            output,
+            ["name", "longest", "current", "first", "last"]
+                .map(String::from)
+                .to_vec(),
+            streaks.iter().map(|(name, streak)| {
+                vec![
+                    name.clone(),
+                    streak.longest.to_string(),
+                    streak.current.to_string(),
+                    streak.first.to_string(),
+                    streak.last.to_string(),
+                ]
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+fn print(streaks: &[(String, Streak)]) {
+    let width = streaks
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, streak) in streaks {
+        let Streak {
+            current,
+            longest,
+            first,
+            last,
+        } = streak;
+        println!("{longest:>3} {current:>3} {name:width$} {first}-{last}");
+    }
+}
+
+pub(crate) fn paths_by_year(directory: &Path) -> Result<BTreeMap<u16, PathBuf>> {
+    let mut paths: BTreeMap<u16, PathBuf> = BTreeMap::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') || entry.file_type()?.is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        if details::is_sidecar(&path) {
+            continue;
+        }
+        let year = match year_from(&path) {
+            Some(year) => Some(year),
+            None => details::sidecar(&path)?.and_then(|details| details.year()),
+        };
+        match year {
+            None => eprintln!("No year in {}, skipping", path.display()),
+            Some(year) => {
+                if let Some(old) = paths.get(&year) {
+                    bail!(
+                        "Both {} and {} are for {year}",
+                        old.display(),
+                        path.display()
+                    );
+                }
+                paths.insert(year, path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+// Returns the last run of exactly four digits in the file stem that
+// looks like a year.
+pub(crate) fn year_from(path: &Path) -> Option<u16> {
+    let stem = path.file_stem()?.to_str()?;
+    stem.split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 4)
+        .filter_map(|digits| digits.parse().ok())
+        .rfind(|year| (1900..2100).contains(year))
+}
+
+enum Edition<'a> {
+    Held(u16, &'a HashSet<String>),
+    Missing,
+}
+
+fn editions<'a>(
+    finishers: &'a BTreeMap<u16, HashSet<String>>,
+    not_held: &[u16],
+) -> Vec<Edition<'a>> {
+    let (Some(first), Some(last)) = (finishers.keys().next(), finishers.keys().next_back()) else {
+        return vec![];
+    };
+    (*first..=*last)
+        .filter(|year| !not_held.contains(year))
+        .map(|year| match finishers.get(&year) {
+            Some(names) => Edition::Held(year, names),
+            None => Edition::Missing,
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct NamedStreak<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    streak: &'a Streak,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Streak {
+    current: u16,
+    longest: u16,
+    first: u16,
+    last: u16,
+}
+
+fn streaks(editions: &[Edition]) -> Vec<(String, Streak)> {
+    let mut streaks: HashMap<&str, (Streak, u16)> = HashMap::new();
+
+    for edition in editions {
+        match edition {
+            Edition::Missing => streaks
+                .values_mut()
+                .for_each(|(streak, _)| streak.current = 0),
+            Edition::Held(year, names) => {
+                for (name, (streak, _)) in streaks.iter_mut() {
+                    if !names.contains(*name) {
+                        streak.current = 0;
+                    }
+                }
+                for name in names.iter() {
+                    let (streak, start) = streaks.entry(name).or_default();
+                    if streak.current == 0 {
+                        *start = *year;
+                    }
+                    streak.current += 1;
+                    if streak.current > streak.longest {
+                        streak.longest = streak.current;
+                        streak.first = *start;
+                        streak.last = *year;
+                    }
+                }
+            }
+        }
+    }
+    streaks
+        .into_iter()
+        .map(|(name, (streak, _))| (name.to_string(), streak))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_year_from() {
+        assert_eq!(
+            year_from(Path::new("assets/wser/WSER 1999.mhtml")),
+            Some(1999)
+        );
+        assert_eq!(
+            year_from(Path::new("assets/lt100/lt100_2013.json")),
+            Some(2013)
+        );
+        assert_eq!(year_from(Path::new("assets/quad/2022.mhtml")), Some(2022));
+        assert_eq!(
+            year_from(Path::new("assets/burque_brew/2024-2025/NOTE")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_streaks() {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<HashSet<_>>();
+        let finishers = BTreeMap::from([
+            (2018, names(&["Ann", "Bob"])),
+            (2019, names(&["Ann", "Bob"])),
+            (2021, names(&["Ann"])),
+            (2022, names(&["Ann", "Bob"])),
+        ]);
+
+        let streaks = |not_held: &[u16]| {
+            streaks(&editions(&finishers, not_held))
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        };
+
+        let with_2020 = streaks(&[2020]);
+        assert_eq!(
+            with_2020["Ann"],
+            Streak {
+                current: 4,
+                longest: 4,
+                first: 2018,
+                last: 2022,
+            }
+        );
+        assert_eq!(
+            with_2020["Bob"],
+            Streak {
+                current: 1,
+                longest: 2,
+                first: 2018,
+                last: 2019,
+            }
+        );
+
+        let without_2020 = streaks(&[]);
+        assert_eq!(
+            without_2020["Ann"],
+            Streak {
+                current: 2,
+                longest: 2,
+                first: 2018,
+                last: 2019,
+            }
+        );
+    }
+}
@@ -0,0 +1,128 @@
+// Results files are full of surprises: genders we've never seen,
+// rows that are missing a field, status codes that aren't documented.
+// Rather than panicking (which used to take down an entire series run)
+// or printing from deep inside a parser, parsers record a Warning and
+// carry on with the rest of the file.  A warning about a row says which
+// row it is, counting from 1 in the order the parser reads the results
+// (so in a CSV file, row 1 is the line after the header), along with
+// the Debug output of whatever the parser was looking at, which is
+// ugly, but is what to search the file for.
+
+use {
+    crate::{RaceResults, pace},
+    anyhow::{Result, bail},
+    serde::{Deserialize, Serialize},
+    std::fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Warning {
+    /// The finisher isn't scored by sex.
+    UnknownGender {
+        gender: String,
+        number: usize,
+        row: String,
+    },
+    /// There's no "place/count" to tell men from women, so the
+    /// finisher isn't scored by sex.
+    NoGenderCount { number: usize, row: String },
+    /// The "place/count" matches neither the men's nor the women's
+    /// count.
+    AssumedNonBinary { number: usize, row: String },
+    /// The row is skipped.
+    MissingField {
+        field: String,
+        number: usize,
+        row: String,
+    },
+    /// The row is skipped.
+    BadField {
+        field: String,
+        error: String,
+        number: usize,
+        row: String,
+    },
+    /// The row is skipped.
+    UnknownStatus {
+        status: u8,
+        number: usize,
+        row: String,
+    },
+    /// Most of the paces that the results list don't agree with the
+    /// race's distance, which is probably wrong.
+    DistanceMismatch {
+        distance: String,
+        agreeing: usize,
+        paces: usize,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        use Warning::*;
+
+        match self {
+            UnknownGender {
+                gender,
+                number,
+                row,
+            } => write!(f, "unknown gender {gender:?} in row {number}: {row}"),
+            NoGenderCount { number, row } => write!(f, "no gender count in row {number}: {row}"),
+            AssumedNonBinary { number, row } => {
+                write!(f, "assuming non-binary in row {number}: {row}")
+            }
+            MissingField { field, number, row } => write!(f, "no {field} in row {number}: {row}"),
+            BadField {
+                field,
+                error,
+                number,
+                row,
+            } => write!(f, "can't convert {field}: {error} in row {number}: {row}"),
+            UnknownStatus {
+                status,
+                number,
+                row,
+            } => write!(f, "unknown status {status} in row {number}: {row}"),
+            DistanceMismatch {
+                distance,
+                agreeing,
+                paces,
+            } => write!(
+                f,
+                "only {agreeing} of {paces} listed paces agree with a distance of {distance}"
+            ),
+        }
+    }
+}
+
+// What the command line does with the warnings from each file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum OnWarning {
+    Print,
+    Fail,
+    Ignore,
+}
+
+impl OnWarning {
+    // Besides the parser's warnings, the listed paces are checked
+    // against the race's distance, which isn't known until the race's
+    // details are.
+    pub(crate) fn check(self, race: &RaceResults) -> Result<()> {
+        if self == OnWarning::Ignore {
+            return Ok(());
+        }
+        let warnings = race
+            .warnings
+            .iter()
+            .cloned()
+            .chain(pace::check(race))
+            .collect::<Vec<_>>();
+        for warning in &warnings {
+            eprintln!("{}: {warning}", race.path.display());
+        }
+        if self == OnWarning::Fail && !warnings.is_empty() {
+            bail!("{} warning(s) in {}", warnings.len(), race.path.display());
+        }
+        Ok(())
+    }
+}